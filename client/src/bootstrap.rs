@@ -0,0 +1,45 @@
+// Single-instruction setup of a new hooked mint, replacing the hand-ordered sequence of
+// mint creation, `initialize_config`, `initialize_mint_stats`, `initialize_vault` and
+// `initialize_extra_account_meta_list`.
+
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::Instruction, system_program, InstructionData,
+    ToAccountMetas,
+};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use transfer_hook::{CreateHookedMintParams, MINT_STATS_SEED, VAULT_AUTHORITY_SEED};
+
+use crate::{config_address, meta_list_address};
+
+pub fn vault_authority_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED, mint.as_ref()], &transfer_hook::ID).0
+}
+
+pub fn royalty_vault_address(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(
+        &vault_authority_address(mint),
+        mint,
+        &anchor_spl::token_2022::ID,
+    )
+}
+
+// Both `authority` and the new `mint` keypair must sign
+pub fn create_hooked_mint_ix(authority: &Pubkey, mint: &Pubkey, params: CreateHookedMintParams) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::CreateHookedMint {
+            authority: *authority,
+            mint: *mint,
+            config: config_address(mint),
+            mint_stats: Pubkey::find_program_address(&[MINT_STATS_SEED, mint.as_ref()], &transfer_hook::ID).0,
+            extra_account_meta_list: meta_list_address(mint),
+            vault_authority: vault_authority_address(mint),
+            royalty_vault: royalty_vault_address(mint),
+            token_program: anchor_spl::token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::CreateHookedMint { params }.data(),
+    }
+}
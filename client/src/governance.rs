@@ -0,0 +1,137 @@
+// Realms-governed configuration.
+//
+// Example proposal flow, lowering the fee cap through a vote:
+//
+// 1. The current authority sends `set_governance_authority_ix` once, handing the config to the
+//    governance (or its native treasury).
+// 2. A member builds the change with `set_fee_bounds_ix(mint, governed_authority, ..)` and wraps
+//    it with `propose_config_change`, which creates the proposal, attaches the instruction and
+//    signs it off for voting.
+// 3. Once the vote passes and the hold-up time elapses, anyone executes the proposal transaction
+//    in Realms, and the governance program signs the `set_*` instruction as the config authority.
+
+use anchor_lang::{
+    prelude::Pubkey, solana_program::instruction::Instruction, InstructionData, ToAccountMetas,
+};
+use spl_governance::{
+    instruction::{create_proposal, insert_transaction, sign_off_proposal},
+    state::{proposal::get_proposal_address, proposal::VoteType, proposal_transaction::InstructionData as GovernanceInstructionData},
+};
+use transfer_hook::{governance_address, native_treasury_address, GovernanceAuthorityParams};
+
+use crate::config_address;
+
+// Account a governance uses to sign as the config authority
+pub fn governed_authority(params: &GovernanceAuthorityParams) -> Pubkey {
+    let governance = governance_address(&params.governance_program, &params.realm, &params.governed_account);
+    if params.use_native_treasury {
+        native_treasury_address(&params.governance_program, &governance)
+    } else {
+        governance
+    }
+}
+
+pub fn set_governance_authority_ix(
+    mint: &Pubkey,
+    current_authority: &Pubkey,
+    params: GovernanceAuthorityParams,
+) -> Instruction {
+    let governance = governance_address(&params.governance_program, &params.realm, &params.governed_account);
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::SetGovernanceAuthority {
+            update: transfer_hook::accounts::UpdateConfig {
+                authority: *current_authority,
+                config: config_address(mint),
+            },
+            realm: params.realm,
+            governance,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::SetGovernanceAuthority { params }.data(),
+    }
+}
+
+pub fn set_fee_bounds_ix(
+    mint: &Pubkey,
+    authority: &Pubkey,
+    min_fee: Option<u64>,
+    max_fee: Option<u64>,
+) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::UpdateConfig {
+            authority: *authority,
+            config: config_address(mint),
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::SetFeeBounds { min_fee, max_fee }.data(),
+    }
+}
+
+// Accounts of the member creating the proposal
+pub struct Proposer {
+    pub token_owner_record: Pubkey,
+    pub governance_authority: Pubkey,
+    pub payer: Pubkey,
+}
+
+// Creates a single-option proposal carrying `config_instruction` and signs it off for voting
+#[allow(clippy::too_many_arguments)]
+pub fn propose_config_change(
+    params: &GovernanceAuthorityParams,
+    governing_token_mint: &Pubkey,
+    proposer: &Proposer,
+    proposal_seed: &Pubkey,
+    name: String,
+    description_link: String,
+    hold_up_time: u32,
+    config_instruction: Instruction,
+) -> Vec<Instruction> {
+    let governance = governance_address(&params.governance_program, &params.realm, &params.governed_account);
+    let proposal = get_proposal_address(
+        &params.governance_program,
+        &governance,
+        governing_token_mint,
+        proposal_seed,
+    );
+
+    vec![
+        create_proposal(
+            &params.governance_program,
+            &governance,
+            &proposer.token_owner_record,
+            &proposer.governance_authority,
+            &proposer.payer,
+            None,
+            &params.realm,
+            name,
+            description_link,
+            governing_token_mint,
+            VoteType::SingleChoice,
+            vec!["Approve".to_string()],
+            true,
+            proposal_seed,
+        ),
+        insert_transaction(
+            &params.governance_program,
+            &governance,
+            &proposal,
+            &proposer.token_owner_record,
+            &proposer.governance_authority,
+            &proposer.payer,
+            0,
+            0,
+            hold_up_time,
+            vec![GovernanceInstructionData::from(config_instruction)],
+        ),
+        sign_off_proposal(
+            &params.governance_program,
+            &params.realm,
+            &governance,
+            &proposal,
+            &proposer.governance_authority,
+            Some(&proposer.token_owner_record),
+        ),
+    ]
+}
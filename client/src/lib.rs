@@ -0,0 +1,17 @@
+// Off-chain helpers for integrators building transactions against the transfer hook program
+
+use anchor_lang::prelude::Pubkey;
+use transfer_hook::{CONFIG_SEED, META_LIST_SEED};
+
+pub mod bootstrap;
+pub mod governance;
+pub mod lookup_table;
+pub mod transfer_log;
+
+pub fn config_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED, mint.as_ref()], &transfer_hook::ID).0
+}
+
+pub fn meta_list_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[META_LIST_SEED, mint.as_ref()], &transfer_hook::ID).0
+}
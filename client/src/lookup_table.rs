@@ -0,0 +1,128 @@
+// Address Lookup Table holding the accounts every hooked transfer of a mint passes. A transfer
+// carries the meta list's extra accounts on top of its own, which doesn't fit a legacy
+// transaction, so integrators compile v0 messages against this table instead.
+//
+// 1. Send each batch of `create_lookup_table_ixs` as its own transaction, in order.
+// 2. Wait one slot after the last extension, tables only serve addresses added in earlier slots.
+// 3. Pass `lookup_table_account(..)` to `v0::Message::try_compile` for the transfer.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        address_lookup_table::instruction::{create_lookup_table, extend_lookup_table},
+        address_lookup_table_account::AddressLookupTableAccount,
+        instruction::Instruction,
+        system_program,
+    },
+};
+use anchor_spl::associated_token;
+use transfer_hook::{
+    CREATOR_LEDGER_SEED, MINT_STATS_SEED, RAFFLE_SEED, RECEIPT_BOOK_SEED, REWARDS_POOL_SEED,
+    ROYALTY_ACCOUNTING_SEED, TRANSFER_LOG_SEED,
+};
+
+use crate::bootstrap::royalty_vault_address;
+use crate::{config_address, meta_list_address};
+
+// Keeps each extend transaction under the packet size with a payer and authority signature
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 20;
+
+// Instructions to set up a mint's table, one inner vec per transaction
+pub struct LookupTableInstructions {
+    pub lookup_table: Pubkey,
+    pub transactions: Vec<Vec<Instruction>>,
+}
+
+fn hook_pda(seed: &[u8], mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[seed, mint.as_ref()], &transfer_hook::ID).0
+}
+
+// Accounts that are the same for every transfer of `mint`. Per-holder PDAs, the receipt page and
+// the configured staking program are left out, pass the latter through `extra` when it is set.
+pub fn static_hook_accounts(mint: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        *mint,
+        transfer_hook::ID,
+        anchor_spl::token_2022::ID,
+        associated_token::ID,
+        system_program::ID,
+        meta_list_address(mint),
+        config_address(mint),
+        royalty_vault_address(mint),
+        hook_pda(MINT_STATS_SEED, mint),
+        hook_pda(TRANSFER_LOG_SEED, mint),
+        hook_pda(REWARDS_POOL_SEED, mint),
+        hook_pda(RAFFLE_SEED, mint),
+        hook_pda(RECEIPT_BOOK_SEED, mint),
+        hook_pda(CREATOR_LEDGER_SEED, mint),
+        hook_pda(ROYALTY_ACCOUNTING_SEED, mint),
+    ]
+}
+
+fn table_addresses(mint: &Pubkey, extra: &[Pubkey]) -> Vec<Pubkey> {
+    let mut addresses = static_hook_accounts(mint);
+    for address in extra {
+        if !addresses.contains(address) {
+            addresses.push(*address);
+        }
+    }
+    addresses
+}
+
+// Creates a table owned by `authority` and fills it with the mint's static accounts plus `extra`.
+// `recent_slot` must be a recent finalized slot, it is part of the table's address.
+pub fn create_lookup_table_ixs(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    recent_slot: u64,
+    extra: &[Pubkey],
+) -> LookupTableInstructions {
+    let (create_ix, lookup_table) = create_lookup_table(*authority, *payer, recent_slot);
+    let addresses = table_addresses(mint, extra);
+
+    let mut transactions = extend_lookup_table_ixs(&lookup_table, authority, payer, &addresses);
+    // The first extension fits next to the creation
+    transactions[0].insert(0, create_ix);
+
+    LookupTableInstructions {
+        lookup_table,
+        transactions,
+    }
+}
+
+// Appends `addresses` to an existing table, split so each batch fits one transaction.
+// Callers are expected to leave out addresses the table already holds.
+pub fn extend_lookup_table_ixs(
+    lookup_table: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Vec<Instruction>> {
+    if addresses.is_empty() {
+        return vec![vec![]];
+    }
+    addresses
+        .chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| {
+            vec![extend_lookup_table(
+                *lookup_table,
+                *authority,
+                Some(*payer),
+                chunk.to_vec(),
+            )]
+        })
+        .collect()
+}
+
+// The table as `v0::Message::try_compile` expects it, assuming every extension has landed
+pub fn lookup_table_account(
+    lookup_table: &Pubkey,
+    mint: &Pubkey,
+    extra: &[Pubkey],
+) -> AddressLookupTableAccount {
+    AddressLookupTableAccount {
+        key: *lookup_table,
+        addresses: table_addresses(mint, extra),
+    }
+}
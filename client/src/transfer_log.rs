@@ -0,0 +1,90 @@
+// Reads a mint's transfer log over RPC, for integrators without an indexer. Fetch the account at
+// `transfer_log_address(mint)` and pass its data to `decode_transfer_log`.
+
+use anchor_lang::{prelude::Pubkey, Discriminator};
+use transfer_hook::{TransferLog, TransferLogEntry, TRANSFER_LOG_SEED};
+
+pub fn transfer_log_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TRANSFER_LOG_SEED, mint.as_ref()], &transfer_hook::ID).0
+}
+
+// Entries oldest first, none when the data isn't a transfer log. RPC responses land in buffers
+// with no alignment guarantee, so everything is copied out instead of cast in place.
+pub fn decode_transfer_log(data: &[u8]) -> Option<Vec<TransferLogEntry>> {
+    if data.len() < TransferLog::HEADER_LEN || data[..8] != TransferLog::DISCRIMINATOR {
+        return None;
+    }
+    let header: TransferLog = bytemuck::pod_read_unaligned(&data[8..TransferLog::HEADER_LEN]);
+    if data.len() < TransferLog::space(header.capacity) {
+        return None;
+    }
+
+    let capacity = header.capacity as u64;
+    let count = header.total_entries.min(capacity);
+    let first = header.total_entries - count;
+
+    Some(
+        (first..header.total_entries)
+            .map(|seq| entry_at(data, (seq % capacity) as usize))
+            .collect(),
+    )
+}
+
+fn entry_at(data: &[u8], index: usize) -> TransferLogEntry {
+    let start = TransferLog::HEADER_LEN + index * TransferLog::ENTRY_LEN;
+    bytemuck::pod_read_unaligned(&data[start..start + TransferLog::ENTRY_LEN])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn entry(slot: u64) -> TransferLogEntry {
+        TransferLogEntry {
+            slot,
+            amount: slot * 10,
+            ..TransferLogEntry::zeroed()
+        }
+    }
+
+    // Log with capacity 2 after three appends, the oldest entry has been overwritten
+    fn wrapped_log() -> Vec<u8> {
+        let mut header = TransferLog::zeroed();
+        header.total_entries = 3;
+        header.capacity = 2;
+
+        let mut data = TransferLog::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&header));
+        data.extend_from_slice(bytemuck::bytes_of(&entry(3)));
+        data.extend_from_slice(bytemuck::bytes_of(&entry(2)));
+        data
+    }
+
+    #[test]
+    fn decodes_oldest_first() {
+        let slots: Vec<u64> = decode_transfer_log(&wrapped_log())
+            .unwrap()
+            .iter()
+            .map(|entry| entry.slot)
+            .collect();
+        assert_eq!(slots, vec![2, 3]);
+    }
+
+    #[test]
+    fn decodes_misaligned_buffer() {
+        let mut buffer = vec![0u8];
+        buffer.extend_from_slice(&wrapped_log());
+
+        let entries = decode_transfer_log(&buffer[1..]).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].slot, 3);
+        assert_eq!(entries[1].amount, 30);
+    }
+
+    #[test]
+    fn rejects_truncated_entries() {
+        let data = wrapped_log();
+        assert!(decode_transfer_log(&data[..data.len() - 1]).is_none());
+    }
+}
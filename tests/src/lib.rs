@@ -0,0 +1,543 @@
+// Helpers for driving the transfer hook through token2022 under solana-program-test.
+//
+// The program is loaded from its compiled `transfer_hook.so`, so run `anchor build` (or
+// `cargo build-sbf --manifest-path transfer-hook/Cargo.toml`) before `cargo test -p transfer-hook-tests`.
+
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account,
+};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    offchain::create_transfer_checked_instruction_with_extra_metas,
+    state::{Account, Mint},
+};
+use transfer_hook::{
+    FeeCredit, InitializeConfigParams, RoundingMode, RoyaltyConfig, TransferHookError, WithdrawalPolicyParams,
+    CONFIG_SEED, CREATOR_LEDGER_SEED, FEE_ACCRUAL_SEED, FEE_CREDIT_SEED, META_LIST_SEED,
+    PENDING_WITHDRAWAL_SEED, REWARDS_POOL_SEED, ROYALTY_ACCOUNTING_SEED, VAULT_AUTHORITY_SEED,
+    WITHDRAWAL_POLICY_SEED,
+};
+
+pub const DECIMALS: u8 = 6;
+
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("transfer_hook", transfer_hook::ID, None);
+    program_test.prefer_bpf(true);
+    program_test
+}
+
+pub fn default_config_params() -> InitializeConfigParams {
+    InitializeConfigParams {
+        distributors: vec![],
+        min_fee_amount: 0,
+        min_fee: None,
+        max_fee: None,
+        rounding: RoundingMode::Floor,
+    }
+}
+
+pub fn config_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED, mint.as_ref()], &transfer_hook::ID).0
+}
+
+pub fn meta_list_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[META_LIST_SEED, mint.as_ref()], &transfer_hook::ID).0
+}
+
+pub fn token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, &spl_token_2022::id())
+}
+
+pub fn vault_authority_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED, mint.as_ref()], &transfer_hook::ID).0
+}
+
+pub fn royalty_vault_address(mint: &Pubkey) -> Pubkey {
+    token_address(&vault_authority_address(mint), mint)
+}
+
+// Sends the instructions paid by the context payer, with any extra signers
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = context.banks_client.get_latest_blockhash().await?;
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+// Simulates the instructions against the current bank and returns the compute units consumed and
+// the program logs, panicking if the transaction would fail
+pub async fn simulate(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> (u64, Vec<String>) {
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    simulation.result.unwrap().unwrap();
+    let details = simulation.simulation_details.unwrap();
+    (details.units_consumed, details.logs)
+}
+
+// Asserts the transaction failed with `error` from this program
+pub fn assert_hook_error(result: Result<(), BanksClientError>, error: TransferHookError) {
+    let code = anchor_lang::error::ERROR_CODE_OFFSET + error as u32;
+    match result.map_err(|err| err.unwrap()) {
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(actual))) => {
+            assert_eq!(actual, code)
+        }
+        other => panic!("expected custom error {code}, got {other:?}"),
+    }
+}
+
+// Creates a token2022 mint with the transfer-hook extension pointing at this program, the payer is
+// both mint authority and hook authority
+pub async fn create_hooked_mint(context: &mut ProgramTestContext, mint: &Keypair) {
+    let payer = context.payer.pubkey();
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook]).unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        spl_token_2022::extension::transfer_hook::instruction::initialize(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            Some(payer),
+            Some(transfer_hook::ID),
+        )
+        .unwrap(),
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            &payer,
+            None,
+            DECIMALS,
+        )
+        .unwrap(),
+    ];
+    process(context, &instructions, &[mint]).await.unwrap();
+}
+
+// A token2022 mint without the transfer-hook extension, what transfers cost without the hook
+pub async fn create_mint(context: &mut ProgramTestContext, mint: &Keypair) {
+    let payer = context.payer.pubkey();
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&[]).unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &spl_token_2022::id(),
+        ),
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::id(),
+            &mint.pubkey(),
+            &payer,
+            None,
+            DECIMALS,
+        )
+        .unwrap(),
+    ];
+    process(context, &instructions, &[mint]).await.unwrap();
+}
+
+pub async fn create_token_account(context: &mut ProgramTestContext, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let instruction = create_associated_token_account(
+        &context.payer.pubkey(),
+        owner,
+        mint,
+        &spl_token_2022::id(),
+    );
+    process(context, &[instruction], &[]).await.unwrap();
+    token_address(owner, mint)
+}
+
+pub async fn mint_to(context: &mut ProgramTestContext, mint: &Pubkey, destination: &Pubkey, amount: u64) {
+    let instruction = spl_token_2022::instruction::mint_to(
+        &spl_token_2022::id(),
+        mint,
+        destination,
+        &context.payer.pubkey(),
+        &[],
+        amount,
+    )
+    .unwrap();
+    process(context, &[instruction], &[]).await.unwrap();
+}
+
+pub async fn token_balance(context: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    StateWithExtensions::<Account>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+// Account data is copied out, banks client buffers carry no alignment guarantee
+pub async fn load_config(context: &mut ProgramTestContext, mint: &Pubkey) -> RoyaltyConfig {
+    let account = context
+        .banks_client
+        .get_account(config_address(mint))
+        .await
+        .unwrap()
+        .unwrap();
+    bytemuck::pod_read_unaligned(&account.data[8..RoyaltyConfig::LEN])
+}
+
+pub fn initialize_config_ix(authority: &Pubkey, mint: &Pubkey, params: InitializeConfigParams) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::InitializeConfig {
+            authority: *authority,
+            mint: *mint,
+            config: config_address(mint),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::InitializeConfig { params }.data(),
+    }
+}
+
+// Admin instruction over the shared `UpdateConfig` accounts
+pub fn update_config_ix(authority: &Pubkey, mint: &Pubkey, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::UpdateConfig {
+            authority: *authority,
+            config: config_address(mint),
+        }
+        .to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn initialize_extra_account_meta_list_ix(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    royalty_token_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::InitializeExtraAccountMetaList {
+            payer: *payer,
+            extra_account_meta_list: meta_list_address(mint),
+            mint: *mint,
+            config: config_address(mint),
+            royalty_token_account: *royalty_token_account,
+            token_program: spl_token_2022::id(),
+            associated_token_program: spl_associated_token_account::id(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::InitializeExtraAccountMetaList {}.data(),
+    }
+}
+
+// Builds a transfer_checked with the hook's extra accounts resolved from the on-chain meta list,
+// the same way wallets do
+pub async fn transfer_checked_ix(
+    context: &mut ProgramTestContext,
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let banks_client = context.banks_client.clone();
+    create_transfer_checked_instruction_with_extra_metas(
+        &spl_token_2022::id(),
+        source,
+        mint,
+        destination,
+        owner,
+        &[],
+        amount,
+        DECIMALS,
+        |address| {
+            let mut banks_client = banks_client.clone();
+            async move {
+                banks_client
+                    .get_account(address)
+                    .await
+                    .map(|account| account.map(|account| account.data))
+                    .map_err(Into::into)
+            }
+        },
+    )
+    .await
+    .unwrap()
+}
+
+pub fn fee_accrual_address(token_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[FEE_ACCRUAL_SEED, token_account.as_ref()], &transfer_hook::ID).0
+}
+
+// Royalties charged on transfers out of `token_account` accrue here until paid
+pub fn initialize_fee_accrual_ix(payer: &Pubkey, mint: &Pubkey, token_account: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::InitializeFeeAccrual {
+            payer: *payer,
+            mint: *mint,
+            token_account: *token_account,
+            fee_accrual: fee_accrual_address(token_account),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::InitializeFeeAccrual {}.data(),
+    }
+}
+
+pub fn initialize_vault_ix(authority: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::InitializeVault {
+            authority: *authority,
+            config: config_address(mint),
+            mint: *mint,
+            vault_authority: vault_authority_address(mint),
+            royalty_vault: royalty_vault_address(mint),
+            extra_account_meta_list: meta_list_address(mint),
+            token_program: spl_token_2022::id(),
+            associated_token_program: spl_associated_token_account::id(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::InitializeVault {}.data(),
+    }
+}
+
+pub fn fee_credit_address(mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[FEE_CREDIT_SEED, mint.as_ref(), owner.as_ref()],
+        &transfer_hook::ID,
+    )
+    .0
+}
+
+pub async fn load_fee_credit(context: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey) -> FeeCredit {
+    let account = context
+        .banks_client
+        .get_account(fee_credit_address(mint, owner))
+        .await
+        .unwrap()
+        .unwrap();
+    bytemuck::pod_read_unaligned(&account.data[8..FeeCredit::LEN])
+}
+
+pub fn initialize_fee_credit_ix(owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::InitializeFeeCredit {
+            owner: *owner,
+            mint: *mint,
+            fee_credit: fee_credit_address(mint, owner),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::InitializeFeeCredit {}.data(),
+    }
+}
+
+// Only credits the deposit, the owner's transfer of `amount` into the vault has to follow it
+pub fn deposit_credits_ix(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    owner_token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::DepositCredits {
+            owner: *owner,
+            config: config_address(mint),
+            mint: *mint,
+            fee_credit: fee_credit_address(mint, owner),
+            owner_token_account: *owner_token_account,
+            fee_accrual: fee_accrual_address(owner_token_account),
+            royalty_vault: royalty_vault_address(mint),
+            royalty_accounting: mint_pda(mint, ROYALTY_ACCOUNTING_SEED),
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token_2022::id(),
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::DepositCredits { amount }.data(),
+    }
+}
+
+fn mint_pda(mint: &Pubkey, seed: &[u8]) -> Pubkey {
+    Pubkey::find_program_address(&[seed, mint.as_ref()], &transfer_hook::ID).0
+}
+
+pub fn pending_withdrawal_address(mint: &Pubkey, id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[PENDING_WITHDRAWAL_SEED, mint.as_ref(), &id.to_le_bytes()],
+        &transfer_hook::ID,
+    )
+    .0
+}
+
+pub fn initialize_withdrawal_policy_ix(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    params: WithdrawalPolicyParams,
+) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::InitializeWithdrawalPolicy {
+            authority: *authority,
+            config: config_address(mint),
+            mint: *mint,
+            withdrawal_policy: mint_pda(mint, WITHDRAWAL_POLICY_SEED),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::InitializeWithdrawalPolicy { params }.data(),
+    }
+}
+
+// Proposes withdrawal `id`, the policy's proposal count at the time
+pub fn propose_withdrawal_ix(
+    proposer: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::ProposeWithdrawal {
+            proposer: *proposer,
+            config: config_address(mint),
+            withdrawal_policy: mint_pda(mint, WITHDRAWAL_POLICY_SEED),
+            pending_withdrawal: pending_withdrawal_address(mint, id),
+            destination: *destination,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::ProposeWithdrawal { amount }.data(),
+    }
+}
+
+pub fn approve_withdrawal_ix(approver: &Pubkey, mint: &Pubkey, id: u64) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::ApproveWithdrawal {
+            approver: *approver,
+            withdrawal_policy: mint_pda(mint, WITHDRAWAL_POLICY_SEED),
+            pending_withdrawal: pending_withdrawal_address(mint, id),
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::ApproveWithdrawal {}.data(),
+    }
+}
+
+// Only approves the executor, the executor's transfer out of the vault has to follow it
+pub fn execute_withdrawal_ix(
+    executor: &Pubkey,
+    mint: &Pubkey,
+    id: u64,
+    proposer: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::ExecuteWithdrawal {
+            executor: *executor,
+            config: config_address(mint),
+            mint: *mint,
+            withdrawal_policy: mint_pda(mint, WITHDRAWAL_POLICY_SEED),
+            pending_withdrawal: pending_withdrawal_address(mint, id),
+            proposer: *proposer,
+            vault_authority: vault_authority_address(mint),
+            royalty_vault: royalty_vault_address(mint),
+            destination: *destination,
+            creator_ledger: mint_pda(mint, CREATOR_LEDGER_SEED),
+            rewards_pool: mint_pda(mint, REWARDS_POOL_SEED),
+            royalty_accounting: mint_pda(mint, ROYALTY_ACCOUNTING_SEED),
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token_2022::id(),
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::ExecuteWithdrawal {}.data(),
+    }
+}
+
+// A hooked mint with config, meta list and a royalty token account owned by `royalty_owner`
+pub struct HookedMint {
+    pub mint: Pubkey,
+    pub royalty_token_account: Pubkey,
+}
+
+pub async fn setup_hooked_mint(
+    context: &mut ProgramTestContext,
+    royalty_owner: &Pubkey,
+    params: InitializeConfigParams,
+) -> HookedMint {
+    let mint = Keypair::new();
+    let payer = context.payer.pubkey();
+
+    create_hooked_mint(context, &mint).await;
+    let mint = mint.pubkey();
+    let royalty_token_account = create_token_account(context, royalty_owner, &mint).await;
+
+    process(
+        context,
+        &[
+            initialize_config_ix(&payer, &mint, params),
+            initialize_extra_account_meta_list_ix(&payer, &mint, &royalty_token_account),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    HookedMint {
+        mint,
+        royalty_token_account,
+    }
+}
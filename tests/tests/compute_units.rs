@@ -0,0 +1,86 @@
+// Compute units of a transfer with and without the hook, printed for comparison. Run with
+// `cargo test -p transfer-hook-tests --test compute_units -- --nocapture` after building the
+// program. The hook's own cost is the difference from the plain token2022 transfer.
+//
+// Per-transfer logging costs compute too. To compare, build the program with
+// `cargo build-sbf --manifest-path transfer-hook/Cargo.toml --features verbose-logs` and run the
+// test with `--features verbose-logs` so it expects the logs.
+
+use solana_sdk::signature::{Keypair, Signer};
+use transfer_hook_tests::*;
+
+#[tokio::test]
+async fn execute_compute_units() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let holder = Keypair::new();
+    let distributor = Keypair::new();
+    let recipient = Keypair::new();
+    let payer = context.payer.pubkey();
+
+    // Baseline: the same transfer of a mint without the hook
+    let plain_mint = Keypair::new();
+    create_mint(&mut context, &plain_mint).await;
+    let plain_mint = plain_mint.pubkey();
+    let plain_source = create_token_account(&mut context, &holder.pubkey(), &plain_mint).await;
+    let plain_destination = create_token_account(&mut context, &recipient.pubkey(), &plain_mint).await;
+    mint_to(&mut context, &plain_mint, &plain_source, 10_000_000).await;
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &plain_source,
+        &plain_mint,
+        &plain_destination,
+        &holder.pubkey(),
+        1_000_000,
+    )
+    .await;
+    let (baseline, _) = simulate(&mut context, &[transfer], &[&holder]).await;
+
+    let mut params = default_config_params();
+    params.distributors = vec![distributor.pubkey()];
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), params).await;
+    let source = create_token_account(&mut context, &holder.pubkey(), &hooked.mint).await;
+    let distributor_source = create_token_account(&mut context, &distributor.pubkey(), &hooked.mint).await;
+    let destination = create_token_account(&mut context, &recipient.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &source, 10_000_000).await;
+    mint_to(&mut context, &hooked.mint, &distributor_source, 10_000_000).await;
+    process(
+        &mut context,
+        &[initialize_fee_accrual_ix(&payer, &hooked.mint, &source)],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Exempt source, no royalty is assessed
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &distributor_source,
+        &hooked.mint,
+        &destination,
+        &distributor.pubkey(),
+        1_000_000,
+    )
+    .await;
+    let (exempt, _) = simulate(&mut context, &[transfer], &[&distributor]).await;
+
+    // Charged transfer, the royalty accrues against the source
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &destination,
+        &holder.pubkey(),
+        1_000_000,
+    )
+    .await;
+    let (charged, logs) = simulate(&mut context, &[transfer], &[&holder]).await;
+    let logged = logs.iter().any(|log| log.contains("Royalty accrued"));
+    assert_eq!(logged, cfg!(feature = "verbose-logs"));
+
+    println!("verbose-logs: {}", cfg!(feature = "verbose-logs"));
+    println!("transfer_checked without hook: {baseline} CU");
+    println!("exempt transfer: {exempt} CU, Execute {} CU", exempt - baseline);
+    println!("charged transfer: {charged} CU, Execute {} CU", charged - baseline);
+    assert!(exempt > baseline && charged > baseline);
+}
@@ -0,0 +1,212 @@
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use transfer_hook::{
+    instruction, CreatorShareEntry, DeadManAction, GovernanceAuthorityParams, PayoutRecipient,
+    PayoutSplitParams, TransferHookError, CREATOR_LEDGER_SEED, LOCK_AUTHORITY, LOCK_FEES,
+    LOCK_RECIPIENTS, LOCK_RESTRICTIONS, PAYOUT_SPLIT_SEED, REWARDS_POOL_SEED,
+    SPL_GOVERNANCE_PROGRAM_ID,
+};
+use transfer_hook_tests::*;
+
+fn hook_pda(seed: &[u8], mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[seed, mint.as_ref()], &transfer_hook::ID).0
+}
+
+fn payout_split_params(recipient: &Pubkey) -> PayoutSplitParams {
+    PayoutSplitParams {
+        recipients: vec![PayoutRecipient {
+            token_account: *recipient,
+            weight_bps: 10_000,
+        }],
+        interval: 0,
+        bounty_bps: 0,
+    }
+}
+
+fn creator_shares() -> Vec<CreatorShareEntry> {
+    vec![CreatorShareEntry {
+        creator: Pubkey::new_unique(),
+        weight_bps: 5_000,
+    }]
+}
+
+// Payout split, creator ledger and rewards pool created while nothing is locked
+async fn setup_recipient_accounts(context: &mut ProgramTestContext, mint: &Pubkey, recipient: &Pubkey) {
+    let authority = context.payer.pubkey();
+    let config = config_address(mint);
+    let rewards_pool = hook_pda(REWARDS_POOL_SEED, mint);
+    let creator_ledger = hook_pda(CREATOR_LEDGER_SEED, mint);
+
+    let instructions = [
+        Instruction {
+            program_id: transfer_hook::ID,
+            accounts: transfer_hook::accounts::InitializePayoutSplit {
+                authority,
+                config,
+                mint: *mint,
+                payout_split: hook_pda(PAYOUT_SPLIT_SEED, mint),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::InitializePayoutSplit {
+                params: payout_split_params(recipient),
+            }
+            .data(),
+        },
+        Instruction {
+            program_id: transfer_hook::ID,
+            accounts: transfer_hook::accounts::InitializeCreatorLedger {
+                authority,
+                config,
+                mint: *mint,
+                creator_ledger,
+                rewards_pool,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::InitializeCreatorLedger {
+                entries: creator_shares(),
+            }
+            .data(),
+        },
+        Instruction {
+            program_id: transfer_hook::ID,
+            accounts: transfer_hook::accounts::InitializeRewardsPool {
+                authority,
+                config,
+                mint: *mint,
+                rewards_pool,
+                pool_token_account: token_address(&rewards_pool, mint),
+                creator_ledger,
+                token_program: spl_token_2022::id(),
+                associated_token_program: spl_associated_token_account::id(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::InitializeRewardsPool { share_bps: 1_000 }.data(),
+        },
+    ];
+    process(context, &instructions, &[]).await.unwrap();
+}
+
+async fn finalize(context: &mut ProgramTestContext, mint: &Pubkey, fields: u16) {
+    let authority = context.payer.pubkey();
+    let finalize = update_config_ix(&authority, mint, instruction::FinalizeConfig { fields });
+    process(context, &[finalize], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn locked_config_rejects_admin_instructions() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let mint = hooked.mint;
+    let authority = context.payer.pubkey();
+    let config = config_address(&mint);
+    setup_recipient_accounts(&mut context, &mint, &hooked.royalty_token_account).await;
+
+    finalize(&mut context, &mint, LOCK_FEES | LOCK_RECIPIENTS | LOCK_RESTRICTIONS).await;
+
+    let locked = [
+        update_config_ix(
+            &authority,
+            &mint,
+            instruction::SetHolderStateGc {
+                holder_state_ttl: 0,
+                gc_rent_destination: authority,
+            },
+        ),
+        Instruction {
+            program_id: transfer_hook::ID,
+            accounts: transfer_hook::accounts::UpdatePayoutSplit {
+                authority,
+                config,
+                payout_split: hook_pda(PAYOUT_SPLIT_SEED, &mint),
+            }
+            .to_account_metas(None),
+            data: instruction::UpdatePayoutSplit {
+                params: payout_split_params(&hooked.royalty_token_account),
+            }
+            .data(),
+        },
+        Instruction {
+            program_id: transfer_hook::ID,
+            accounts: transfer_hook::accounts::UpdateCreatorLedger {
+                authority,
+                config,
+                creator_ledger: hook_pda(CREATOR_LEDGER_SEED, &mint),
+                rewards_pool: hook_pda(REWARDS_POOL_SEED, &mint),
+            }
+            .to_account_metas(None),
+            data: instruction::SetCreatorShares {
+                entries: creator_shares(),
+            }
+            .data(),
+        },
+        Instruction {
+            program_id: transfer_hook::ID,
+            accounts: transfer_hook::accounts::UpdateRewardsPool {
+                authority,
+                config,
+                rewards_pool: hook_pda(REWARDS_POOL_SEED, &mint),
+                creator_ledger: hook_pda(CREATOR_LEDGER_SEED, &mint),
+            }
+            .to_account_metas(None),
+            data: instruction::SetRewardsShare { share_bps: 500 }.data(),
+        },
+        update_config_ix(&authority, &mint, instruction::SetSnapshotCrank { enabled: true }),
+        update_config_ix(
+            &authority,
+            &mint,
+            instruction::SetDeadManSwitch {
+                epochs: 10,
+                action: DeadManAction::Relax,
+            },
+        ),
+    ];
+    for instruction in locked {
+        let result = process(&mut context, &[instruction], &[]).await;
+        assert_hook_error(result, TransferHookError::ConfigLocked);
+    }
+}
+
+#[tokio::test]
+async fn renounced_config_rejects_governance_handover() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let authority = context.payer.pubkey();
+
+    finalize(&mut context, &hooked.mint, LOCK_AUTHORITY).await;
+
+    let handover = Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::SetGovernanceAuthority {
+            update: transfer_hook::accounts::UpdateConfig {
+                authority,
+                config: config_address(&hooked.mint),
+            },
+            realm: Pubkey::new_unique(),
+            governance: Pubkey::new_unique(),
+        }
+        .to_account_metas(None),
+        data: instruction::SetGovernanceAuthority {
+            params: GovernanceAuthorityParams {
+                governance_program: SPL_GOVERNANCE_PROGRAM_ID,
+                realm: Pubkey::new_unique(),
+                governed_account: Pubkey::new_unique(),
+                use_native_treasury: false,
+            },
+        }
+        .data(),
+    };
+    let result = process(&mut context, &[handover], &[]).await;
+
+    // The authority is renounced along with the lock, so the signer no longer matches
+    assert_hook_error(result, TransferHookError::Unauthorized);
+}
@@ -0,0 +1,52 @@
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+use transfer_hook::TransferHookError;
+use transfer_hook_tests::*;
+
+#[tokio::test]
+async fn deposit_credits_the_owner_transfer() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let holder = Keypair::new();
+
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let payer = context.payer.pubkey();
+    process(&mut context, &[initialize_vault_ix(&payer, &hooked.mint)], &[])
+        .await
+        .unwrap();
+    let vault = royalty_vault_address(&hooked.mint);
+    let source = create_token_account(&mut context, &holder.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &source, 1_000_000).await;
+    // The holder pays the credit account's rent
+    process(
+        &mut context,
+        &[system_instruction::transfer(&payer, &holder.pubkey(), LAMPORTS_PER_SOL)],
+        &[],
+    )
+    .await
+    .unwrap();
+    process(
+        &mut context,
+        &[initialize_fee_credit_ix(&holder.pubkey(), &hooked.mint)],
+        &[&holder],
+    )
+    .await
+    .unwrap();
+
+    // Crediting without the transfer that funds it is rejected
+    let deposit = deposit_credits_ix(&holder.pubkey(), &hooked.mint, &source, 200_000);
+    let result = process(&mut context, &[deposit.clone()], &[&holder]).await;
+    assert_hook_error(result, TransferHookError::OutflowTransferMissing);
+
+    let transfer =
+        transfer_checked_ix(&mut context, &source, &hooked.mint, &vault, &holder.pubkey(), 200_000).await;
+    process(&mut context, &[deposit, transfer], &[&holder]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &vault).await, 200_000);
+    let credit = load_fee_credit(&mut context, &hooked.mint, &holder.pubkey()).await;
+    assert_eq!(credit.balance, 200_000);
+    assert_eq!(credit.total_deposited, 200_000);
+}
@@ -0,0 +1,76 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::{
+    account::AccountSharedData,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+use transfer_hook_tests::*;
+
+// Execute discriminator, TLV length and meta count precede the 35-byte metas
+const META_LIST_HEADER_LEN: usize = 16;
+const META_LEN: usize = 35;
+
+// Lists created before `migrate_config` existed resolved the recipient, token program and config
+const DEPLOYED_META_COUNT: usize = 3;
+
+fn migrate_meta_list_ix(payer: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: transfer_hook::ID,
+        accounts: transfer_hook::accounts::MigrateExtraAccountMetaList {
+            payer: *payer,
+            config: config_address(mint),
+            mint: *mint,
+            extra_account_meta_list: meta_list_address(mint),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: transfer_hook::instruction::MigrateExtraAccountMetaList {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn deployed_meta_list_grows_to_the_current_layout() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let address = meta_list_address(&hooked.mint);
+    let current = context.banks_client.get_account(address).await.unwrap().unwrap();
+
+    // Cut the list back to what a deployed mint holds, with rent for that size only
+    let deployed_len = META_LIST_HEADER_LEN + DEPLOYED_META_COUNT * META_LEN;
+    let mut deployed = current.clone();
+    deployed.data.truncate(deployed_len);
+    deployed.data[8..12].copy_from_slice(&((deployed_len - 12) as u32).to_le_bytes());
+    deployed.data[12..16].copy_from_slice(&(DEPLOYED_META_COUNT as u32).to_le_bytes());
+    let rent = context.banks_client.get_rent().await.unwrap();
+    deployed.lamports = rent.minimum_balance(deployed_len);
+    context.set_account(&address, &AccountSharedData::from(deployed));
+
+    let payer = context.payer.pubkey();
+    process(&mut context, &[migrate_meta_list_ix(&payer, &hooked.mint)], &[])
+        .await
+        .unwrap();
+
+    let migrated = context.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(migrated.data, current.data);
+    assert!(migrated.lamports >= rent.minimum_balance(migrated.data.len()));
+}
+
+#[tokio::test]
+async fn current_meta_list_migrates_in_place() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let address = meta_list_address(&hooked.mint);
+    let before = context.banks_client.get_account(address).await.unwrap().unwrap();
+
+    let payer = context.payer.pubkey();
+    process(&mut context, &[migrate_meta_list_ix(&payer, &hooked.mint)], &[])
+        .await
+        .unwrap();
+
+    let after = context.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(after.data, before.data);
+}
@@ -0,0 +1,91 @@
+use solana_sdk::signature::{Keypair, Signer};
+use transfer_hook::{TransferHookError, WithdrawalPolicyParams};
+use transfer_hook_tests::*;
+
+// Vault payouts approve the signer, who moves the tokens in the next instruction so the hook runs
+// under token2022 without re-entering the program
+#[tokio::test]
+async fn withdrawal_is_paid_by_the_executor_transfer() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let treasury = Keypair::new();
+
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let payer = context.payer.pubkey();
+    process(&mut context, &[initialize_vault_ix(&payer, &hooked.mint)], &[])
+        .await
+        .unwrap();
+    let vault = royalty_vault_address(&hooked.mint);
+    let destination = create_token_account(&mut context, &treasury.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &vault, 1_000_000).await;
+
+    let policy = WithdrawalPolicyParams {
+        approvers: vec![payer],
+        threshold: 1,
+        timelock: 0,
+    };
+    process(
+        &mut context,
+        &[
+            initialize_withdrawal_policy_ix(&payer, &hooked.mint, policy),
+            propose_withdrawal_ix(&payer, &hooked.mint, 0, &destination, 400_000),
+            approve_withdrawal_ix(&payer, &hooked.mint, 0),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Approving without the transfer that spends it is rejected
+    let execute = execute_withdrawal_ix(&payer, &hooked.mint, 0, &payer, &destination);
+    let result = process(&mut context, &[execute.clone()], &[]).await;
+    assert_hook_error(result, TransferHookError::OutflowTransferMissing);
+
+    let transfer =
+        transfer_checked_ix(&mut context, &vault, &hooked.mint, &destination, &payer, 400_000).await;
+    process(&mut context, &[execute, transfer], &[]).await.unwrap();
+
+    assert_eq!(token_balance(&mut context, &destination).await, 400_000);
+    assert_eq!(token_balance(&mut context, &vault).await, 600_000);
+}
+
+#[tokio::test]
+async fn withdrawal_rejects_a_different_transfer() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let treasury = Keypair::new();
+    let other = Keypair::new();
+
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let payer = context.payer.pubkey();
+    process(&mut context, &[initialize_vault_ix(&payer, &hooked.mint)], &[])
+        .await
+        .unwrap();
+    let vault = royalty_vault_address(&hooked.mint);
+    let destination = create_token_account(&mut context, &treasury.pubkey(), &hooked.mint).await;
+    let elsewhere = create_token_account(&mut context, &other.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &vault, 1_000_000).await;
+
+    let policy = WithdrawalPolicyParams {
+        approvers: vec![payer],
+        threshold: 1,
+        timelock: 0,
+    };
+    process(
+        &mut context,
+        &[
+            initialize_withdrawal_policy_ix(&payer, &hooked.mint, policy),
+            propose_withdrawal_ix(&payer, &hooked.mint, 0, &destination, 400_000),
+            approve_withdrawal_ix(&payer, &hooked.mint, 0),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // The allowance can't be redirected to another account
+    let execute = execute_withdrawal_ix(&payer, &hooked.mint, 0, &payer, &destination);
+    let transfer = transfer_checked_ix(&mut context, &vault, &hooked.mint, &elsewhere, &payer, 400_000).await;
+    let result = process(&mut context, &[execute, transfer], &[]).await;
+    assert_hook_error(result, TransferHookError::OutflowTransferMissing);
+}
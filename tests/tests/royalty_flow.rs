@@ -0,0 +1,234 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use transfer_hook::TransferHookError;
+use transfer_hook_tests::*;
+
+#[tokio::test]
+async fn transfer_charges_royalty() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let holder = Keypair::new();
+    let recipient = Keypair::new();
+
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let source = create_token_account(&mut context, &holder.pubkey(), &hooked.mint).await;
+    let destination = create_token_account(&mut context, &recipient.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &source, 10_000_000).await;
+    let payer = context.payer.pubkey();
+    process(
+        &mut context,
+        &[initialize_fee_accrual_ix(&payer, &hooked.mint, &source)],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // The royalty accrues on the sale and the holder pays it right after
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &destination,
+        &holder.pubkey(),
+        1_000_000,
+    )
+    .await;
+    let payment = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &hooked.royalty_token_account,
+        &holder.pubkey(),
+        50_000,
+    )
+    .await;
+    process(&mut context, &[transfer, payment], &[&holder]).await.unwrap();
+
+    // 5% of the transfer lands in the royalty account
+    assert_eq!(
+        token_balance(&mut context, &hooked.royalty_token_account).await,
+        50_000
+    );
+    assert_eq!(token_balance(&mut context, &destination).await, 1_000_000);
+}
+
+#[tokio::test]
+async fn unpaid_royalty_blocks_the_next_transfer() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let holder = Keypair::new();
+    let recipient = Keypair::new();
+
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let source = create_token_account(&mut context, &holder.pubkey(), &hooked.mint).await;
+    let destination = create_token_account(&mut context, &recipient.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &source, 10_000_000).await;
+    let payer = context.payer.pubkey();
+    process(
+        &mut context,
+        &[initialize_fee_accrual_ix(&payer, &hooked.mint, &source)],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &destination,
+        &holder.pubkey(),
+        1_000_000,
+    )
+    .await;
+    process(&mut context, &[transfer], &[&holder]).await.unwrap();
+
+    let next = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &destination,
+        &holder.pubkey(),
+        1_000,
+    )
+    .await;
+    let result = process(&mut context, &[next], &[&holder]).await;
+    assert_hook_error(result, TransferHookError::RoyaltiesUnsettled);
+}
+
+#[tokio::test]
+async fn charged_transfer_requires_fee_accrual() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let holder = Keypair::new();
+    let recipient = Keypair::new();
+
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let source = create_token_account(&mut context, &holder.pubkey(), &hooked.mint).await;
+    let destination = create_token_account(&mut context, &recipient.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &source, 10_000_000).await;
+
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &destination,
+        &holder.pubkey(),
+        1_000_000,
+    )
+    .await;
+    let result = process(&mut context, &[transfer], &[&holder]).await;
+    assert_hook_error(result, TransferHookError::FeeAccrualRequired);
+}
+
+#[tokio::test]
+async fn distributor_transfer_skips_royalty() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let distributor = Keypair::new();
+    let recipient = Keypair::new();
+
+    let mut params = default_config_params();
+    params.distributors = vec![distributor.pubkey()];
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), params).await;
+    let source = create_token_account(&mut context, &distributor.pubkey(), &hooked.mint).await;
+    let destination = create_token_account(&mut context, &recipient.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &source, 10_000_000).await;
+
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &destination,
+        &distributor.pubkey(),
+        1_000_000,
+    )
+    .await;
+    process(&mut context, &[transfer], &[&distributor]).await.unwrap();
+
+    assert_eq!(
+        token_balance(&mut context, &hooked.royalty_token_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn transfer_below_threshold_skips_royalty() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let holder = Keypair::new();
+    let recipient = Keypair::new();
+
+    let mut params = default_config_params();
+    params.min_fee_amount = 1_000;
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), params).await;
+    let source = create_token_account(&mut context, &holder.pubkey(), &hooked.mint).await;
+    let destination = create_token_account(&mut context, &recipient.pubkey(), &hooked.mint).await;
+    mint_to(&mut context, &hooked.mint, &source, 10_000).await;
+
+    let transfer = transfer_checked_ix(
+        &mut context,
+        &source,
+        &hooked.mint,
+        &destination,
+        &holder.pubkey(),
+        999,
+    )
+    .await;
+    process(&mut context, &[transfer], &[&holder]).await.unwrap();
+
+    assert_eq!(
+        token_balance(&mut context, &hooked.royalty_token_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn config_rejects_floor_above_cap() {
+    let mut context = program_test().start_with_context().await;
+    let mint = Keypair::new();
+    create_hooked_mint(&mut context, &mint).await;
+
+    let mut params = default_config_params();
+    params.min_fee = Some(10);
+    params.max_fee = Some(5);
+    let payer = context.payer.pubkey();
+    let result = process(
+        &mut context,
+        &[initialize_config_ix(&payer, &mint.pubkey(), params)],
+        &[],
+    )
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn initialized_vault_becomes_the_recipient() {
+    let mut context = program_test().start_with_context().await;
+    let royalty_owner = Keypair::new();
+    let hooked = setup_hooked_mint(&mut context, &royalty_owner.pubkey(), default_config_params()).await;
+    let authority = context.payer.pubkey();
+
+    process(&mut context, &[initialize_vault_ix(&authority, &hooked.mint)], &[])
+        .await
+        .unwrap();
+
+    let vault = royalty_vault_address(&hooked.mint);
+    let config = load_config(&mut context, &hooked.mint).await;
+    assert_eq!(config.royalty_vault, vault);
+    assert_eq!(config.royalty_recipient, vault);
+
+    // The meta list now resolves the vault instead of the old recipient
+    let meta_list = context
+        .banks_client
+        .get_account(meta_list_address(&hooked.mint))
+        .await
+        .unwrap()
+        .unwrap();
+    let holds = |key: &Pubkey| meta_list.data.windows(32).any(|window| window == key.as_ref());
+    assert!(holds(&vault));
+    assert!(!holds(&hooked.royalty_token_account));
+}
@@ -0,0 +1,417 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+
+pub const ROYALTY_ACCOUNTING_SEED: &[u8] = b"royalty-accounting";
+
+// Where vault tokens came from or went, as recorded by the instructions moving them
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VaultFlow {
+    // Prepaid fee credits
+    Deposit,
+    // Transfer-fee extension withholdings
+    Harvest,
+    // Any payout signed by the vault authority
+    Withdrawal,
+}
+
+// Per-mint accounting trail of royalties, from assessment by the hook to leaving the vault.
+// Every royalty the hook assesses is either drawn from credits or accrued against the source
+// account, and the vault should hold what was recorded going in less what was recorded going out.
+// `reconcile` reports any difference.
+#[account(zero_copy)]
+pub struct RoyaltyAccounting {
+    pub total_assessed: u64,
+    // Moved to the recipient during the transfer by versions before royalties were accrued
+    pub total_collected: u64,
+    // Paid from prepaid fee credits already in the vault
+    pub total_credited: u64,
+    // Accrued against source token accounts until their owners pay
+    pub total_deferred: u64,
+    // Accruals paid to the recipient since. Includes accruals from before this account existed.
+    pub total_settled: u64,
+    // Vault balance when this account was created, flows before it aren't itemized
+    pub opening_balance: u64,
+    pub vault_royalties: u64,
+    pub vault_deposits: u64,
+    pub vault_harvested: u64,
+    pub vault_withdrawn: u64,
+    pub reconcile_count: u64,
+    pub last_reconciled_ts: i64,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl RoyaltyAccounting {
+    pub const LEN: usize = 8 + std::mem::size_of::<RoyaltyAccounting>();
+
+    // `charged` is the royalty the hook assessed less drawn credits, all of it accrued
+    pub fn record_transfer(&mut self, charged: u64, credited: u64) {
+        self.total_assessed = self.total_assessed.saturating_add(charged).saturating_add(credited);
+        self.total_credited = self.total_credited.saturating_add(credited);
+        self.total_deferred = self.total_deferred.saturating_add(charged);
+    }
+
+    // `received` is what reached the vault, zero for a recipient outside it. It can fall short of
+    // `settled` when the transfer-fee extension withholds part of the payment.
+    pub fn record_settlement(&mut self, settled: u64, received: u64) {
+        self.total_settled = self.total_settled.saturating_add(settled);
+        self.vault_royalties = self.vault_royalties.saturating_add(received);
+    }
+
+    pub fn record_vault_flow(&mut self, flow: VaultFlow, amount: u64) {
+        match flow {
+            VaultFlow::Deposit => self.vault_deposits = self.vault_deposits.saturating_add(amount),
+            VaultFlow::Harvest => self.vault_harvested = self.vault_harvested.saturating_add(amount),
+            VaultFlow::Withdrawal => self.vault_withdrawn = self.vault_withdrawn.saturating_add(amount),
+        }
+    }
+
+    // Assessed royalties not accounted for as collected, credited or accrued. Only a bug or a
+    // saturated counter makes it non-zero.
+    pub fn unaccounted(&self) -> i128 {
+        self.total_assessed as i128
+            - self.total_collected as i128
+            - self.total_credited as i128
+            - self.total_deferred as i128
+    }
+
+    pub fn expected_vault_balance(&self) -> i128 {
+        self.opening_balance as i128
+            + self.vault_royalties as i128
+            + self.vault_deposits as i128
+            + self.vault_harvested as i128
+            - self.vault_withdrawn as i128
+    }
+
+    // Negative when the vault holds less than recorded. Surpluses are tokens sent to the vault
+    // outside the program and aren't a leak.
+    pub fn vault_discrepancy(&self, vault_balance: u64) -> i128 {
+        vault_balance as i128 - self.expected_vault_balance()
+    }
+
+    // Checked whenever royalties are settled. `vault_balance` is given where the vault is at hand.
+    pub fn check_invariants(&self, vault_balance: Option<u64>) -> Result<()> {
+        require!(
+            self.unaccounted() == 0,
+            TransferHookError::AccountingInvariantViolated
+        );
+        if let Some(vault_balance) = vault_balance {
+            require!(
+                self.vault_discrepancy(vault_balance) >= 0,
+                TransferHookError::AccountingInvariantViolated
+            );
+        }
+
+        Ok(())
+    }
+}
+
+// Result of `reconcile`, returned as Borsh-encoded return data and mirrored in `RoyaltiesReconciled`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Reconciliation {
+    pub mint: Pubkey,
+    pub total_assessed: u64,
+    pub total_collected: u64,
+    pub total_credited: u64,
+    pub total_deferred: u64,
+    pub total_settled: u64,
+    pub unaccounted: i128,
+    pub expected_vault_balance: i128,
+    // Zero until the vault is initialized
+    pub vault_balance: u64,
+    pub vault_discrepancy: i128,
+}
+
+#[event]
+pub struct RoyaltiesReconciled {
+    pub reconciliation: Reconciliation,
+    pub timestamp: i64,
+}
+
+// Emitted next to `RoyaltiesReconciled` when assessed royalties are unaccounted for or the vault
+// holds less than recorded
+#[event]
+pub struct AccountingDiscrepancy {
+    pub mint: Pubkey,
+    pub unaccounted: i128,
+    pub vault_discrepancy: i128,
+}
+
+fn load_if_initialized<'info>(
+    royalty_accounting: &AccountInfo<'info>,
+    mint: &Pubkey,
+) -> Result<Option<AccountLoader<'info, RoyaltyAccounting>>> {
+    if royalty_accounting.owner != &crate::ID || royalty_accounting.data_len() != RoyaltyAccounting::LEN {
+        return Ok(None);
+    }
+
+    let loader = AccountLoader::<RoyaltyAccounting>::try_from(royalty_accounting)?;
+    require_keys_eq!(
+        loader.load()?.mint,
+        *mint,
+        TransferHookError::InvalidRoyaltyAccounting
+    );
+    Ok(Some(loader))
+}
+
+// The hook receives the accounting PDA on every transfer whether or not it exists. `received` is
+// what of `settled` reached the vault, and `vault_balance` is given when it is non-zero so the
+// books are checked against it.
+pub fn record_transfer_if_initialized(
+    royalty_accounting: &AccountInfo,
+    mint: &Pubkey,
+    charged: u64,
+    credited: u64,
+    settled: u64,
+    received: u64,
+    vault_balance: Option<u64>,
+) -> Result<()> {
+    let Some(loader) = load_if_initialized(royalty_accounting, mint)? else {
+        return Ok(());
+    };
+
+    let accounting = &mut loader.load_mut()?;
+    accounting.record_transfer(charged, credited);
+    if settled > 0 {
+        accounting.record_settlement(settled, received);
+    }
+    accounting.check_invariants(vault_balance)
+}
+
+// Vault inflows and outflows outside the hook and settlement, by balance change where fees could
+// apply
+pub fn record_vault_flow_if_initialized(
+    royalty_accounting: &AccountInfo,
+    mint: &Pubkey,
+    flow: VaultFlow,
+    amount: u64,
+) -> Result<()> {
+    let Some(loader) = load_if_initialized(royalty_accounting, mint)? else {
+        return Ok(());
+    };
+
+    loader.load_mut()?.record_vault_flow(flow, amount);
+
+    Ok(())
+}
+
+// Zero before `initialize_vault`. Once the vault exists it must be passed, an omitted vault would
+// read as empty.
+fn vault_balance(
+    config: &AccountLoader<RoyaltyConfig>,
+    royalty_vault: &Option<InterfaceAccount<TokenAccount>>,
+) -> Result<u64> {
+    match royalty_vault {
+        Some(royalty_vault) => Ok(royalty_vault.amount),
+        None => {
+            require_keys_eq!(
+                RoyaltyConfig::load_current(config)?.royalty_vault,
+                Pubkey::default(),
+                TransferHookError::InvalidRoyaltyRecipient
+            );
+            Ok(0)
+        }
+    }
+}
+
+// Permissionless, whoever creates it pays the rent. The vault's balance at this point opens the
+// books, omit the vault before `initialize_vault`.
+pub fn initialize_royalty_accounting(ctx: Context<InitializeRoyaltyAccounting>) -> Result<()> {
+    let opening_balance = vault_balance(&ctx.accounts.config, &ctx.accounts.royalty_vault)?;
+    let accounting = &mut ctx.accounts.royalty_accounting.load_init()?;
+    accounting.mint = ctx.accounts.mint.key();
+    accounting.opening_balance = opening_balance;
+    accounting.bump = ctx.bumps.royalty_accounting;
+
+    Ok(())
+}
+
+// Permissionless: compares the books with the vault and reports the result as return data and
+// events. Discrepancies are surfaced, not corrected.
+pub fn reconcile(ctx: Context<Reconcile>) -> Result<Reconciliation> {
+    let vault_balance = vault_balance(&ctx.accounts.config, &ctx.accounts.royalty_vault)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    let accounting = &mut ctx.accounts.royalty_accounting.load_mut()?;
+    accounting.reconcile_count = accounting.reconcile_count.saturating_add(1);
+    accounting.last_reconciled_ts = now;
+
+    let reconciliation = Reconciliation {
+        mint: accounting.mint,
+        total_assessed: accounting.total_assessed,
+        total_collected: accounting.total_collected,
+        total_credited: accounting.total_credited,
+        total_deferred: accounting.total_deferred,
+        total_settled: accounting.total_settled,
+        unaccounted: accounting.unaccounted(),
+        expected_vault_balance: accounting.expected_vault_balance(),
+        vault_balance,
+        vault_discrepancy: accounting.vault_discrepancy(vault_balance),
+    };
+
+    if reconciliation.unaccounted != 0 || reconciliation.vault_discrepancy < 0 {
+        emit!(AccountingDiscrepancy {
+            mint: reconciliation.mint,
+            unaccounted: reconciliation.unaccounted,
+            vault_discrepancy: reconciliation.vault_discrepancy,
+        });
+    }
+    emit!(RoyaltiesReconciled {
+        reconciliation: reconciliation.clone(),
+        timestamp: now,
+    });
+
+    Ok(reconciliation)
+}
+
+#[derive(Accounts)]
+pub struct InitializeRoyaltyAccounting<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        space = RoyaltyAccounting::LEN,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub royalty_accounting: AccountLoader<'info, RoyaltyAccounting>,
+    // Omit before `initialize_vault`
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+    )]
+    pub royalty_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, config.load()?.mint.as_ref()],
+        bump = royalty_accounting.load()?.bump,
+    )]
+    pub royalty_accounting: AccountLoader<'info, RoyaltyAccounting>,
+    // Omit before `initialize_vault`
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+    )]
+    pub royalty_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    fn books(opening_balance: u64) -> RoyaltyAccounting {
+        let mut accounting = RoyaltyAccounting::zeroed();
+        accounting.opening_balance = opening_balance;
+        accounting
+    }
+
+    #[test]
+    fn paid_royalties_reconcile_with_the_vault() {
+        let mut accounting = books(500);
+        accounting.record_transfer(100, 0);
+        accounting.record_settlement(100, 100);
+        accounting.record_transfer(40, 10);
+        accounting.record_settlement(40, 40);
+
+        assert_eq!(accounting.total_assessed, 150);
+        assert_eq!(accounting.total_deferred, 140);
+        assert_eq!(accounting.total_credited, 10);
+        assert_eq!(accounting.unaccounted(), 0);
+        assert_eq!(accounting.expected_vault_balance(), 640);
+        assert_eq!(accounting.vault_discrepancy(640), 0);
+        assert!(accounting.check_invariants(Some(640)).is_ok());
+    }
+
+    #[test]
+    fn accrued_royalties_are_expected_once_paid() {
+        let mut accounting = books(0);
+        accounting.record_transfer(30, 0);
+        assert_eq!(accounting.total_deferred, 30);
+        assert_eq!(accounting.expected_vault_balance(), 0);
+
+        // One payment covers this transfer's royalty and the earlier one
+        accounting.record_transfer(20, 0);
+        accounting.record_settlement(50, 50);
+        assert_eq!(accounting.total_settled, 50);
+        assert_eq!(accounting.unaccounted(), 0);
+        assert_eq!(accounting.expected_vault_balance(), 50);
+        assert!(accounting.check_invariants(Some(50)).is_ok());
+    }
+
+    #[test]
+    fn vault_outflows_reduce_the_expected_balance() {
+        let mut accounting = books(0);
+        accounting.record_transfer(100, 0);
+        accounting.record_settlement(100, 100);
+        accounting.record_vault_flow(VaultFlow::Deposit, 50);
+        accounting.record_vault_flow(VaultFlow::Harvest, 5);
+        accounting.record_vault_flow(VaultFlow::Withdrawal, 80);
+
+        assert_eq!(accounting.expected_vault_balance(), 75);
+    }
+
+    #[test]
+    fn royalties_paid_outside_the_vault_are_not_expected_in_it() {
+        let mut accounting = books(0);
+        accounting.record_transfer(100, 0);
+        accounting.record_settlement(100, 0);
+
+        assert_eq!(accounting.total_settled, 100);
+        assert_eq!(accounting.expected_vault_balance(), 0);
+    }
+
+    #[test]
+    fn shortfall_fails_the_invariants_and_surplus_does_not() {
+        let mut accounting = books(0);
+        accounting.record_transfer(100, 0);
+        accounting.record_settlement(100, 100);
+
+        assert_eq!(accounting.vault_discrepancy(90), -10);
+        assert!(accounting.check_invariants(Some(90)).is_err());
+        // Tokens sent to the vault outside the program
+        assert_eq!(accounting.vault_discrepancy(120), 20);
+        assert!(accounting.check_invariants(Some(120)).is_ok());
+    }
+
+    #[test]
+    fn withheld_transfer_fee_is_not_expected_in_the_vault() {
+        // 100 settled into the vault with 2 withheld by the transfer-fee extension
+        let mut gross = books(0);
+        gross.record_transfer(100, 0);
+        gross.record_settlement(100, 100);
+        assert!(gross.check_invariants(Some(98)).is_err());
+
+        let mut net = books(0);
+        net.record_transfer(100, 0);
+        net.record_settlement(100, 98);
+        assert_eq!(net.total_settled, 100);
+        assert_eq!(net.vault_discrepancy(98), 0);
+        assert!(net.check_invariants(Some(98)).is_ok());
+    }
+
+    #[test]
+    fn unaccounted_assessment_fails_the_invariants() {
+        let mut accounting = books(0);
+        accounting.record_transfer(100, 0);
+        accounting.total_assessed += 1;
+
+        assert_eq!(accounting.unaccounted(), 1);
+        assert!(accounting.check_invariants(None).is_err());
+    }
+}
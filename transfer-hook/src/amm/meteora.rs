@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hash, pubkey};
+
+use super::{read_pubkey, AmmAdapter};
+
+// Meteora DLMM `LbPair`, an Anchor account whose reserves follow the two mints
+const RESERVE_X_OFFSET: usize = 152;
+const RESERVE_Y_OFFSET: usize = 184;
+
+pub struct MeteoraDlmmAdapter;
+
+impl AmmAdapter for MeteoraDlmmAdapter {
+    const PROGRAM_ID: Pubkey = pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
+
+    fn reserve_vaults(data: &[u8]) -> Option<[Pubkey; 2]> {
+        if data.len() < 8 || data[..8] != hash(b"account:LbPair").to_bytes()[..8] {
+            return None;
+        }
+        Some([
+            read_pubkey(data, RESERVE_X_OFFSET)?,
+            read_pubkey(data, RESERVE_Y_OFFSET)?,
+        ])
+    }
+}
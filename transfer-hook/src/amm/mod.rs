@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+
+#[cfg(feature = "meteora-dlmm-adapter")]
+pub mod meteora;
+#[cfg(feature = "orca-whirlpool-adapter")]
+pub mod orca;
+#[cfg(feature = "raydium-amm-adapter")]
+pub mod raydium;
+
+pub const AMM_POOL_SEED: &[u8] = b"amm-pool";
+
+// Reads a supported AMM's pool state. Each AMM gets an adapter behind its own Cargo feature.
+pub trait AmmAdapter {
+    const PROGRAM_ID: Pubkey;
+
+    // The pool's reserve token accounts, `None` when the data isn't a pool of this AMM
+    fn reserve_vaults(data: &[u8]) -> Option<[Pubkey; 2]>;
+}
+
+pub(crate) fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    let bytes: [u8; 32] = data.get(offset..offset + 32)?.try_into().ok()?;
+    Some(Pubkey::new_from_array(bytes))
+}
+
+fn reserve_vaults_with<A: AmmAdapter>(pool: &AccountInfo) -> Result<Option<[Pubkey; 2]>> {
+    if pool.owner != &A::PROGRAM_ID {
+        return Ok(None);
+    }
+    Ok(A::reserve_vaults(&pool.try_borrow_data()?))
+}
+
+// Reserve vaults of `pool` according to whichever enabled adapter owns it
+#[allow(unused_variables, unused_mut)]
+pub fn reserve_vaults(pool: &AccountInfo) -> Result<Option<[Pubkey; 2]>> {
+    let mut vaults = None;
+    #[cfg(feature = "raydium-amm-adapter")]
+    if vaults.is_none() {
+        vaults = reserve_vaults_with::<raydium::RaydiumAmmAdapter>(pool)?;
+    }
+    #[cfg(feature = "orca-whirlpool-adapter")]
+    if vaults.is_none() {
+        vaults = reserve_vaults_with::<orca::OrcaWhirlpoolAdapter>(pool)?;
+    }
+    #[cfg(feature = "meteora-dlmm-adapter")]
+    if vaults.is_none() {
+        vaults = reserve_vaults_with::<meteora::MeteoraDlmmAdapter>(pool)?;
+    }
+    Ok(vaults)
+}
+
+// Which side of a pool a transfer is on, from the hooked mint's point of view
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TradeSide {
+    Transfer,
+    // Tokens leave a pool vault
+    Buy,
+    // Tokens enter a pool vault
+    Sell,
+}
+
+// A token account verified to be a reserve vault of an AMM pool for the mint
+#[account]
+pub struct AmmPool {
+    pub mint: Pubkey,
+    pub pool: Pubkey,
+    pub amm_program: Pubkey,
+    pub vault: Pubkey,
+    pub bump: u8,
+}
+
+impl AmmPool {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 1;
+
+    // The hook receives the pool PDA of both token accounts, pool vaults are the ones where it
+    // exists and is ours
+    pub fn is_registered(amm_pool: &AccountInfo) -> Result<bool> {
+        if amm_pool.owner != &crate::ID || amm_pool.data_len() < 8 {
+            return Ok(false);
+        }
+        Ok(amm_pool.try_borrow_data()?[..8] == AmmPool::DISCRIMINATOR)
+    }
+}
+
+pub fn trade_side(source_pool: &AccountInfo, destination_pool: &AccountInfo) -> Result<TradeSide> {
+    if AmmPool::is_registered(source_pool)? {
+        Ok(TradeSide::Buy)
+    } else if AmmPool::is_registered(destination_pool)? {
+        Ok(TradeSide::Sell)
+    } else {
+        Ok(TradeSide::Transfer)
+    }
+}
+
+// Permissionless: the pool state itself proves the vault belongs to the AMM, so a fake pool
+// account can't get a token account classified as one
+pub fn register_amm_pool(ctx: Context<RegisterAmmPool>) -> Result<()> {
+    let pool = ctx.accounts.pool.to_account_info();
+    let vault = ctx.accounts.pool_vault.key();
+    let vaults = reserve_vaults(&pool)?.ok_or(TransferHookError::InvalidAmmPool)?;
+    require!(vaults.contains(&vault), TransferHookError::InvalidAmmPool);
+
+    let amm_pool = &mut ctx.accounts.amm_pool;
+    amm_pool.mint = ctx.accounts.mint.key();
+    amm_pool.pool = pool.key();
+    amm_pool.amm_program = *pool.owner;
+    amm_pool.vault = vault;
+    amm_pool.bump = ctx.bumps.amm_pool;
+
+    msg!("Registered pool vault {} of AMM {}", vault, amm_pool.amm_program);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterAmmPool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: pool state, owner and layout checked by the adapter
+    pub pool: UncheckedAccount<'info>,
+    #[account(
+        token::mint = mint,
+    )]
+    pub pool_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = AmmPool::LEN,
+        seeds = [AMM_POOL_SEED, pool_vault.key().as_ref()],
+        bump
+    )]
+    pub amm_pool: Account<'info, AmmPool>,
+    pub system_program: Program<'info, System>,
+}
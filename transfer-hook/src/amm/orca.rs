@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hash, pubkey};
+
+use super::{read_pubkey, AmmAdapter};
+
+// Orca Whirlpools `Whirlpool`, an Anchor account
+const WHIRLPOOL_LEN: usize = 653;
+const TOKEN_VAULT_A_OFFSET: usize = 133;
+const TOKEN_VAULT_B_OFFSET: usize = 213;
+
+pub struct OrcaWhirlpoolAdapter;
+
+impl AmmAdapter for OrcaWhirlpoolAdapter {
+    const PROGRAM_ID: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+    fn reserve_vaults(data: &[u8]) -> Option<[Pubkey; 2]> {
+        if data.len() != WHIRLPOOL_LEN || data[..8] != hash(b"account:Whirlpool").to_bytes()[..8] {
+            return None;
+        }
+        Some([
+            read_pubkey(data, TOKEN_VAULT_A_OFFSET)?,
+            read_pubkey(data, TOKEN_VAULT_B_OFFSET)?,
+        ])
+    }
+}
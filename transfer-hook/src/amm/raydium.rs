@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+use super::{read_pubkey, AmmAdapter};
+
+// Raydium AMM v4 `AmmInfo`, a fixed-size account without discriminator
+const AMM_INFO_LEN: usize = 752;
+const COIN_VAULT_OFFSET: usize = 336;
+const PC_VAULT_OFFSET: usize = 368;
+
+pub struct RaydiumAmmAdapter;
+
+impl AmmAdapter for RaydiumAmmAdapter {
+    const PROGRAM_ID: Pubkey = pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+
+    fn reserve_vaults(data: &[u8]) -> Option<[Pubkey; 2]> {
+        if data.len() != AMM_INFO_LEN {
+            return None;
+        }
+        Some([
+            read_pubkey(data, COIN_VAULT_OFFSET)?,
+            read_pubkey(data, PC_VAULT_OFFSET)?,
+        ])
+    }
+}
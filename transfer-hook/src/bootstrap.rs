@@ -0,0 +1,144 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::program::invoke,
+    system_program::{create_account, CreateAccount},
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken, Create},
+    token_2022::{
+        spl_token_2022::{
+            extension::{transfer_hook::instruction::initialize as initialize_transfer_hook, ExtensionType},
+            instruction::initialize_mint2,
+            state::Mint as MintState,
+        },
+        Token2022,
+    },
+};
+
+use crate::config::{InitializeConfigParams, RoyaltyConfig, CONFIG_SEED};
+use crate::meta_list::{self, META_LIST_SEED};
+use crate::stats::{MintStats, MINT_STATS_SEED};
+use crate::vault::VAULT_AUTHORITY_SEED;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateHookedMintParams {
+    pub decimals: u8,
+    pub config: InitializeConfigParams,
+}
+
+// Everything a new mint needs in one instruction: the Token-2022 mint with its transfer-hook
+// extension pointing here, config, mint stats, the royalty vault as recipient, and the meta list.
+// The authority becomes mint authority, hook authority and config authority.
+pub fn create_hooked_mint(ctx: Context<CreateHookedMint>, params: CreateHookedMintParams) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+    let mint = ctx.accounts.mint.key();
+    let token_program = ctx.accounts.token_program.key();
+
+    let space = ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferHook])?;
+    create_account(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            CreateAccount {
+                from: ctx.accounts.authority.to_account_info(),
+                to: ctx.accounts.mint.to_account_info(),
+            },
+        ),
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        &token_program,
+    )?;
+    // Extensions are initialized before the mint itself
+    invoke(
+        &initialize_transfer_hook(&token_program, &mint, Some(authority), Some(crate::ID))?,
+        &[ctx.accounts.mint.to_account_info()],
+    )?;
+    invoke(
+        &initialize_mint2(&token_program, &mint, &authority, None, params.decimals)?,
+        &[ctx.accounts.mint.to_account_info()],
+    )?;
+
+    associated_token::create(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        Create {
+            payer: ctx.accounts.authority.to_account_info(),
+            associated_token: ctx.accounts.royalty_vault.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    let stats = &mut ctx.accounts.mint_stats.load_init()?;
+    stats.mint = mint;
+    stats.rolling_updated_ts = Clock::get()?.unix_timestamp;
+    stats.bump = ctx.bumps.mint_stats;
+
+    // The discriminator is only written on exit, so the meta list is built from the config in hand
+    let config = &mut ctx.accounts.config.load_init()?;
+    config.init(authority, mint, &params.config, ctx.bumps.config)?;
+    config.royalty_vault = ctx.accounts.royalty_vault.key();
+    config.vault_authority_bump = ctx.bumps.vault_authority;
+    config.royalty_recipient = config.royalty_vault;
+
+    let account_metas = meta_list::extra_account_metas(config, &token_program)?;
+    meta_list::create_extra_account_meta_list(
+        &ctx.accounts.authority.to_account_info(),
+        &ctx.accounts.extra_account_meta_list.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &mint,
+        ctx.bumps.extra_account_meta_list,
+        &account_metas,
+    )?;
+
+    msg!("Created hooked mint {}", mint);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateHookedMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    // New mint keypair, created and initialized in the handler
+    #[account(mut)]
+    pub mint: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = RoyaltyConfig::LEN,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        init,
+        payer = authority,
+        space = MintStats::LEN,
+        seeds = [MINT_STATS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub mint_stats: AccountLoader<'info, MintStats>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [META_LIST_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    /// CHECK: PDA owning the royalty vault, holds no data
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: royalty vault ATA, created in the handler once the mint exists
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&vault_authority.key(), &mint.key(), &token_program.key()),
+    )]
+    pub royalty_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+use crate::validation;
+
+pub const AMOUNT_BUCKET_SEED: &[u8] = b"amount-bucket";
+
+// Buckets drop the low bytes of the amount, so each covers 2^32 base units. The remaining high
+// bytes of the little-endian amount are taken straight from the Execute instruction data.
+pub const AMOUNT_BUCKET_LOW_BYTES: usize = 4;
+
+// Offset of the amount in Execute instruction data, after the 8-byte discriminator
+pub const EXECUTE_AMOUNT_OFFSET: usize = 8;
+
+pub fn bucket_of(amount: u64) -> u64 {
+    amount >> (8 * AMOUNT_BUCKET_LOW_BYTES)
+}
+
+// Seed bytes identifying `bucket`, as they appear in the Execute instruction data
+pub fn bucket_seed(bucket: u64) -> Vec<u8> {
+    (bucket << (8 * AMOUNT_BUCKET_LOW_BYTES)).to_le_bytes()[AMOUNT_BUCKET_LOW_BYTES..].to_vec()
+}
+
+// Transfer counts per amount range, written by the hook for the bucket of each transfer
+#[account(zero_copy)]
+pub struct AmountBucket {
+    pub bucket: u64,
+    pub transfer_count: u64,
+    pub total_volume: u64,
+    pub total_fees: u64,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl AmountBucket {
+    pub const LEN: usize = 8 + std::mem::size_of::<AmountBucket>();
+}
+
+// The hook receives the PDA of the transfer's bucket whether or not it has been created
+pub fn record_if_initialized(
+    amount_bucket: &AccountInfo,
+    mint: &Pubkey,
+    amount: u64,
+    fee: u64,
+) -> Result<()> {
+    if amount_bucket.owner != &crate::ID || amount_bucket.data_len() != AmountBucket::LEN {
+        return Ok(());
+    }
+
+    let loader = AccountLoader::<AmountBucket>::try_from(amount_bucket)?;
+    let bucket = &mut loader.load_mut()?;
+    let bucket_seed = bucket_seed(bucket_of(amount));
+    validation::require_derived(
+        amount_bucket,
+        &[AMOUNT_BUCKET_SEED, mint.as_ref(), &bucket_seed],
+        bucket.bump,
+    )?;
+    bucket.transfer_count = bucket.transfer_count.saturating_add(1);
+    bucket.total_volume = bucket.total_volume.saturating_add(amount);
+    bucket.total_fees = bucket.total_fees.saturating_add(fee);
+
+    Ok(())
+}
+
+// Permissionless, the payer funds the counter for one bucket
+pub fn initialize_amount_bucket(ctx: Context<InitializeAmountBucket>, bucket: u64) -> Result<()> {
+    require!(
+        bucket <= bucket_of(u64::MAX),
+        TransferHookError::InvalidAmountBucket
+    );
+
+    let amount_bucket = &mut ctx.accounts.amount_bucket.load_init()?;
+    amount_bucket.bucket = bucket;
+    amount_bucket.mint = ctx.accounts.mint.key();
+    amount_bucket.bump = ctx.bumps.amount_bucket;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(bucket: u64)]
+pub struct InitializeAmountBucket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        space = AmountBucket::LEN,
+        seeds = [AMOUNT_BUCKET_SEED, mint.key().as_ref(), &bucket_seed(bucket)],
+        bump
+    )]
+    pub amount_bucket: AccountLoader<'info, AmountBucket>,
+    pub system_program: Program<'info, System>,
+}
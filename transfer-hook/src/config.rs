@@ -0,0 +1,1003 @@
+use std::cell::Ref;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use bytemuck::Zeroable;
+
+use crate::amm::TradeSide;
+use crate::enforcement::{RULE_ACCOUNT_LOCK, RULE_DENY_LIST};
+use crate::error::TransferHookError;
+use crate::exchange::ExchangePolicy;
+use crate::fees::{self, RoundingMode};
+use crate::meta_list::{self, META_LIST_SEED};
+use crate::stake::STAKE_SEED_PREFIX_CAPACITY;
+
+pub const CONFIG_SEED: &[u8] = b"royalty-config";
+
+// Bumped whenever the layout grows. Fields are only ever appended after `version` so its offset
+// is stable across layouts, and appended fields must treat zero as their default so that
+// `migrate_config` can upgrade old accounts by reallocating with zeroed bytes.
+pub const CONFIG_VERSION: u8 = 23;
+
+// Upper bound on distributor accounts so the config has a fixed allocation
+pub const MAX_DISTRIBUTORS: usize = 8;
+
+pub const MAX_FEE_SEGMENTS: usize = 8;
+
+pub const MAX_VOLUME_TIERS: usize = 4;
+
+// `finalize_config` bits, each permanently freezing a group of settings
+// Rates, bounds, thresholds, rounding, exemptions, allow list and exchange policy, the hook never charges above the locked rate
+pub const LOCK_FEES: u16 = 1 << 0;
+// Royalty recipient, vault, distribution program, payout split, creator shares and rewards share
+pub const LOCK_RECIPIENTS: u16 = 1 << 1;
+// Delegate and confidential policies, stake integration, rule enforcement, new account locks, deny
+// list additions, the snapshot crank and the dead-man switch
+pub const LOCK_RESTRICTIONS: u16 = 1 << 2;
+// Renounces the authority, which blocks every admin instruction including governance handover.
+// Account locks and deny list entries drop to monitor-only since nobody could lift them.
+pub const LOCK_AUTHORITY: u16 = 1 << 3;
+pub const LOCK_ALL: u16 = LOCK_FEES | LOCK_RECIPIENTS | LOCK_RESTRICTIONS | LOCK_AUTHORITY;
+
+// From `start_ts` onwards the royalty is `fee_bps`, until the next segment starts
+#[zero_copy]
+pub struct FeeSegment {
+    pub start_ts: i64,
+    pub fee_bps: u16,
+    pub _padding: [u8; 6],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct FeeScheduleEntry {
+    pub start_ts: i64,
+    pub fee_bps: u16,
+}
+
+// Sellers whose lifetime outbound volume is at least `min_volume` pay `fee_bps`
+#[zero_copy]
+pub struct VolumeTier {
+    pub min_volume: u64,
+    pub fee_bps: u16,
+    pub _padding: [u8; 6],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct VolumeTierEntry {
+    pub min_volume: u64,
+    pub fee_bps: u16,
+}
+
+// Read on every transfer, so the layout is fixed and accessed in place rather than Borsh decoded.
+// Fields are ordered by alignment so the struct has no implicit padding.
+#[account(zero_copy)]
+pub struct RoyaltyConfig {
+    // Transfers below this amount are not charged royalties
+    pub min_fee_amount: u64,
+    // Absolute per-transfer floor and cap applied to the percentage fee, 0 means unset
+    pub min_fee: u64,
+    pub max_fee: u64,
+    // Minimum seconds between royalty recipient rotations
+    pub rotation_cooldown: i64,
+    pub last_rotation_ts: i64,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    // Program-owned ATA receiving royalties, default until `initialize_vault` runs
+    pub royalty_vault: Pubkey,
+    // Token account receiving royalties, mirrored in the extra account meta list
+    pub royalty_recipient: Pubkey,
+    // Proposed recipient awaiting acceptance by its owner, default when none
+    pub pending_recipient: Pubkey,
+    // Source owners that skip royalties, e.g. airdrop or initial distribution wallets
+    pub distributors: [Pubkey; MAX_DISTRIBUTORS],
+    pub distributor_count: u8,
+    pub rounding: u8,
+    pub bump: u8,
+    pub vault_authority_bump: u8,
+    pub version: u8,
+    pub _padding: [u8; 3],
+    // v2: seconds of inactivity before an empty holder PDA can be closed, 0 disables collection
+    pub holder_state_ttl: i64,
+    // v2: receives rent from collected holder PDAs, default pays the cranker
+    pub gc_rent_destination: Pubkey,
+    // v3: extra fee on permanent-delegate transfers when the policy is `Surcharge`
+    pub delegate_surcharge_bps: u16,
+    // v3: `DelegatePolicy` for transfers moved by the mint's permanent delegate
+    pub delegate_policy: u8,
+    pub _padding_v3: [u8; 5],
+    // v4: fee charged on confidential transfers when the policy is `FlatFee`
+    pub confidential_flat_fee: u64,
+    // v4: `ConfidentialPolicy` for transfers whose amount is encrypted
+    pub confidential_policy: u8,
+    pub _padding_v4: [u8; 7],
+    // v5: running total of transfer-fee extension withholdings harvested into the vault
+    pub total_extension_fees_harvested: u64,
+    // v5: when set, the hook subtracts the transfer-fee extension's fee from its royalty
+    pub net_extension_fee: u8,
+    pub _padding_v5: [u8; 7],
+    // v6: compare fee thresholds against the interest-scaled UI amount of interest-bearing mints
+    pub ui_amount_thresholds: u8,
+    pub _padding_v6: [u8; 7],
+    // v7: launch-phase fee curve, segments sorted by start time
+    pub fee_schedule: [FeeSegment; MAX_FEE_SEGMENTS],
+    pub fee_segment_count: u8,
+    pub _padding_v7: [u8; 7],
+    // v8: volume-reactive fee controller, fed by `MintStats` rolling volume
+    pub dynamic_volume_window: i64,
+    pub dynamic_volume_target: u64,
+    pub dynamic_min_fee_bps: u16,
+    pub dynamic_max_fee_bps: u16,
+    pub dynamic_fee_enabled: u8,
+    pub _padding_v8: [u8; 3],
+    // v9: downstream program `forward_royalties` sends vault balances to
+    pub distribution_program: Pubkey,
+    // v10: external staking program whose stake records cap what an owner may transfer out
+    pub stake_program: Pubkey,
+    pub stake_seed_prefix: [u8; STAKE_SEED_PREFIX_CAPACITY],
+    pub stake_amount_offset: u16,
+    pub stake_seed_prefix_len: u8,
+    pub stake_seed_includes_mint: u8,
+    pub stake_check_enabled: u8,
+    pub _padding_v10: [u8; 3],
+    // v11: Realms governance holding the authority, default when not governed
+    pub governance_program: Pubkey,
+    pub governance: Pubkey,
+    // v12: balance snapshots, 0 until the first `take_snapshot`
+    pub current_snapshot_id: u64,
+    pub last_snapshot_epoch: u64,
+    // Lets anyone take a snapshot once per epoch
+    pub snapshot_crank_enabled: u8,
+    pub _padding_v12: [u8; 7],
+    // v13: owners holding at least one token of this mint skip royalties, default when unset
+    pub exemption_pass_mint: Pubkey,
+    // v13: token program of the pass mint, part of the pass ATA derivation
+    pub exemption_pass_token_program: Pubkey,
+    // v14: progressive rates by the seller's lifetime outbound volume, sorted by threshold
+    pub volume_tiers: [VolumeTier; MAX_VOLUME_TIERS],
+    pub volume_tier_count: u8,
+    pub _padding_v14: [u8; 7],
+    // v15: `LOCK_*` bits set by `finalize_config`, never cleared
+    pub locked_fields: u16,
+    // v15: highest scheduled or trade rate when fees were locked, the hook caps the effective rate at it
+    pub locked_fee_bps: u16,
+    pub _padding_v15: [u8; 4],
+    // v16: fee charged on transfers into exchange deposit owners when the policy is `FlatFee`
+    pub exchange_flat_fee: u64,
+    // v16: `ExchangePolicy` for transfers into registered exchange deposit owners
+    pub exchange_policy: u8,
+    pub _padding_v16: [u8; 7],
+    // v17: rates replacing the scheduled rate on buys from and sells into registered AMM pools
+    pub buy_fee_bps: u16,
+    pub sell_fee_bps: u16,
+    pub trade_fees_enabled: u8,
+    pub _padding_v17: [u8; 3],
+    // v18: `RULE_*` bits the hook only reports instead of enforcing
+    pub monitor_only_rules: u16,
+    pub _padding_v18: [u8; 6],
+    // v19: skip royalties on wrap and unwrap moves of native-wrapped mints, see `MintClass`
+    pub native_wrap_exempt: u8,
+    pub _padding_v19: [u8; 7],
+    // v20: epochs without an admin action before the dead-man switch can trip, zero disables it
+    pub dead_man_epochs: u64,
+    // v20: epoch of the authority's last action through an admin context
+    pub last_admin_epoch: u64,
+    // v20: `DeadManAction` applied when the switch trips
+    pub dead_man_action: u8,
+    pub _padding_v20: [u8; 7],
+    // v21: let royalties accrued by a token account add up until paid, as long as its balance covers them
+    pub lazy_settlement: u8,
+    pub _padding_v21: [u8; 7],
+    // v22: `PROGRAM_DESTINATION_*` bits applied when the destination owner is a PDA
+    pub program_destination_overrides: u8,
+    pub _padding_v22: [u8; 7],
+    // v23: `DistributionAdapterKind` used by `forward_royalties` for `distribution_program`
+    pub distribution_adapter: u8,
+    pub _padding_v23: [u8; 7],
+}
+
+// Per-transfer inputs to fee assessment
+pub struct FeeInputs {
+    pub amount: u64,
+    // Amount thresholds are compared against, the UI amount when `ui_amount_thresholds` is set
+    pub threshold_amount: u64,
+    pub now: i64,
+    pub source_owner: Pubkey,
+    pub by_permanent_delegate: bool,
+    // Source owner is on the allow list
+    pub allow_listed: bool,
+    // Source owner holds the configured exemption pass
+    pub holds_exemption_pass: bool,
+    // Destination owner is a registered exchange deposit owner
+    pub to_exchange: bool,
+    // Destination is the royalty vault or recipient, e.g. a fee credit deposit or paying accrued royalties
+    pub to_royalty_account: bool,
+    // Source is the royalty vault or the rewards pool, paying out royalties already collected
+    pub from_royalty_vault: bool,
+    // Destination owner is a PDA and such destinations are exempt
+    pub to_exempt_program: bool,
+    // Wrap or unwrap move of a native-wrapped mint, only classified when the policy is enabled
+    pub native_wrap: bool,
+    // Buy or sell against a registered AMM pool, only classified when trade fees are enabled
+    pub trade_side: TradeSide,
+    // Mint rolling volume including this transfer, when stats are tracked
+    pub rolling_volume: Option<u64>,
+    // Seller's outbound volume before this transfer, zero without holder stats
+    pub lifetime_volume: u64,
+}
+
+// How transfers initiated by the mint's permanent delegate rather than the owner are treated
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DelegatePolicy {
+    // Charged like any other transfer
+    Allow,
+    Block,
+    // Charged the regular royalty plus `delegate_surcharge_bps`
+    Surcharge,
+}
+
+// Confidential transfers carry no usable amount, so percentage fees can't be computed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ConfidentialPolicy {
+    // Let the transfer through without fees
+    Skip,
+    Block,
+    // Charge `confidential_flat_fee` from the source's public balance
+    FlatFee,
+}
+
+impl ConfidentialPolicy {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ConfidentialPolicy::Block,
+            2 => ConfidentialPolicy::FlatFee,
+            _ => ConfidentialPolicy::Skip,
+        }
+    }
+}
+
+impl DelegatePolicy {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => DelegatePolicy::Block,
+            2 => DelegatePolicy::Surcharge,
+            _ => DelegatePolicy::Allow,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitializeConfigParams {
+    pub distributors: Vec<Pubkey>,
+    pub min_fee_amount: u64,
+    pub min_fee: Option<u64>,
+    pub max_fee: Option<u64>,
+    pub rounding: RoundingMode,
+}
+
+impl RoyaltyConfig {
+    pub const LEN: usize = 8 + std::mem::size_of::<RoyaltyConfig>();
+
+    // Load the config only if it has the current layout, so stale accounts fail loudly instead of
+    // being misread
+    pub fn load_current<'a>(loader: &'a AccountLoader<'_, RoyaltyConfig>) -> Result<Ref<'a, RoyaltyConfig>> {
+        require!(
+            loader.as_ref().data_len() == Self::LEN,
+            TransferHookError::ConfigVersionMismatch
+        );
+        let config = loader.load()?;
+        require!(
+            config.version == CONFIG_VERSION,
+            TransferHookError::ConfigVersionMismatch
+        );
+        Ok(config)
+    }
+
+    // Fill a freshly allocated config
+    pub fn init(&mut self, authority: Pubkey, mint: Pubkey, params: &InitializeConfigParams, bump: u8) -> Result<()> {
+        self.authority = authority;
+        self.mint = mint;
+        self.set_distributors(&params.distributors)?;
+        self.min_fee_amount = params.min_fee_amount;
+        self.set_fee_bounds(params.min_fee, params.max_fee)?;
+        self.rounding = params.rounding as u8;
+        self.bump = bump;
+        self.version = CONFIG_VERSION;
+        Ok(())
+    }
+
+    pub fn is_locked(&self, fields: u16) -> bool {
+        self.locked_fields & fields != 0
+    }
+
+    // Adds `fields` to the permanent locks, see `finalize_config`
+    pub fn lock(&mut self, fields: u16, now: i64) {
+        let newly_locked = fields & !self.locked_fields;
+        if newly_locked & LOCK_FEES != 0 {
+            self.locked_fee_bps = [TradeSide::Transfer, TradeSide::Buy, TradeSide::Sell]
+                .into_iter()
+                .map(|side| self.base_fee_bps(now, side))
+                .max()
+                .unwrap_or_default();
+        }
+        if newly_locked & LOCK_AUTHORITY != 0 {
+            // Nobody can sign as the default key
+            self.authority = Pubkey::default();
+            // Account locks and deny list entries could never be lifted again, stop enforcing them
+            self.monitor_only_rules |= RULE_ACCOUNT_LOCK | RULE_DENY_LIST;
+        }
+        self.locked_fields |= fields;
+    }
+
+    // Fails if any of `fields` has been finalized
+    pub fn require_unlocked(&self, fields: u16) -> Result<()> {
+        require!(!self.is_locked(fields), TransferHookError::ConfigLocked);
+        Ok(())
+    }
+
+    pub fn distributors(&self) -> &[Pubkey] {
+        &self.distributors[..self.distributor_count as usize]
+    }
+
+    pub fn set_distributors(&mut self, distributors: &[Pubkey]) -> Result<()> {
+        require!(
+            distributors.len() <= MAX_DISTRIBUTORS,
+            TransferHookError::TooManyDistributors
+        );
+
+        self.distributors = [Pubkey::default(); MAX_DISTRIBUTORS];
+        self.distributors[..distributors.len()].copy_from_slice(distributors);
+        self.distributor_count = distributors.len() as u8;
+        Ok(())
+    }
+
+    pub fn min_fee(&self) -> Option<u64> {
+        (self.min_fee != 0).then_some(self.min_fee)
+    }
+
+    pub fn max_fee(&self) -> Option<u64> {
+        (self.max_fee != 0).then_some(self.max_fee)
+    }
+
+    pub fn set_fee_bounds(&mut self, min_fee: Option<u64>, max_fee: Option<u64>) -> Result<()> {
+        if let (Some(min_fee), Some(max_fee)) = (min_fee, max_fee) {
+            require!(min_fee <= max_fee, TransferHookError::InvalidFeeBounds);
+        }
+        // A zero cap would be indistinguishable from no cap
+        require!(max_fee != Some(0), TransferHookError::InvalidFeeBounds);
+
+        self.min_fee = min_fee.unwrap_or(0);
+        self.max_fee = max_fee.unwrap_or(0);
+        Ok(())
+    }
+
+    pub fn fee_schedule(&self) -> &[FeeSegment] {
+        &self.fee_schedule[..self.fee_segment_count as usize]
+    }
+
+    pub fn set_fee_schedule(&mut self, entries: &[FeeScheduleEntry]) -> Result<()> {
+        require!(
+            entries.len() <= MAX_FEE_SEGMENTS,
+            TransferHookError::InvalidFeeSchedule
+        );
+        require!(
+            entries.windows(2).all(|pair| pair[0].start_ts < pair[1].start_ts),
+            TransferHookError::InvalidFeeSchedule
+        );
+        require!(
+            entries
+                .iter()
+                .all(|entry| entry.fee_bps as u64 <= fees::BPS_DENOMINATOR),
+            TransferHookError::InvalidBps
+        );
+
+        self.fee_schedule = [FeeSegment::zeroed(); MAX_FEE_SEGMENTS];
+        for (segment, entry) in self.fee_schedule.iter_mut().zip(entries) {
+            segment.start_ts = entry.start_ts;
+            segment.fee_bps = entry.fee_bps;
+        }
+        self.fee_segment_count = entries.len() as u8;
+        Ok(())
+    }
+
+    // Rate of the latest segment that has started, the base rate before the first one
+    pub fn fee_bps_at(&self, now: i64) -> u16 {
+        self.fee_schedule()
+            .iter()
+            .rev()
+            .find(|segment| segment.start_ts <= now)
+            .map_or(fees::BASE_FEE_BPS, |segment| segment.fee_bps)
+    }
+
+    pub fn volume_tiers(&self) -> &[VolumeTier] {
+        &self.volume_tiers[..self.volume_tier_count as usize]
+    }
+
+    pub fn set_volume_tiers(&mut self, entries: &[VolumeTierEntry]) -> Result<()> {
+        require!(
+            entries.len() <= MAX_VOLUME_TIERS,
+            TransferHookError::InvalidVolumeTiers
+        );
+        require!(
+            entries.windows(2).all(|pair| pair[0].min_volume < pair[1].min_volume),
+            TransferHookError::InvalidVolumeTiers
+        );
+        require!(
+            entries
+                .iter()
+                .all(|entry| entry.fee_bps as u64 <= fees::BPS_DENOMINATOR),
+            TransferHookError::InvalidBps
+        );
+
+        self.volume_tiers = [VolumeTier::zeroed(); MAX_VOLUME_TIERS];
+        for (tier, entry) in self.volume_tiers.iter_mut().zip(entries) {
+            tier.min_volume = entry.min_volume;
+            tier.fee_bps = entry.fee_bps;
+        }
+        self.volume_tier_count = entries.len() as u8;
+        Ok(())
+    }
+
+    // Side-specific rate for pool trades when enabled, the scheduled rate otherwise
+    pub fn base_fee_bps(&self, now: i64, trade_side: TradeSide) -> u16 {
+        match trade_side {
+            TradeSide::Buy if self.trade_fees_enabled != 0 => self.buy_fee_bps,
+            TradeSide::Sell if self.trade_fees_enabled != 0 => self.sell_fee_bps,
+            _ => self.fee_bps_at(now),
+        }
+    }
+
+    // Rate of the highest tier the seller has reached, the given base rate below the first one
+    pub fn tiered_fee_bps(&self, base_bps: u16, lifetime_volume: u64) -> u16 {
+        self.volume_tiers()
+            .iter()
+            .rev()
+            .find(|tier| tier.min_volume <= lifetime_volume)
+            .map_or(base_bps, |tier| tier.fee_bps)
+    }
+
+    pub fn stake_seed_prefix(&self) -> &[u8] {
+        &self.stake_seed_prefix[..self.stake_seed_prefix_len as usize]
+    }
+
+    pub fn rounding(&self) -> RoundingMode {
+        RoundingMode::from_u8(self.rounding)
+    }
+
+    // Transfers sent by the mint authority or a distributor are not charged royalties
+    pub fn is_exempt_source(&self, owner: &Pubkey, mint: &Mint) -> bool {
+        mint.mint_authority == COption::Some(*owner) || self.distributors().contains(owner)
+    }
+
+    pub fn rotation_allowed(&self, now: i64) -> bool {
+        now.saturating_sub(self.last_rotation_ts) >= self.rotation_cooldown
+    }
+
+    pub fn delegate_policy(&self) -> DelegatePolicy {
+        DelegatePolicy::from_u8(self.delegate_policy)
+    }
+
+    pub fn confidential_policy(&self) -> ConfidentialPolicy {
+        ConfidentialPolicy::from_u8(self.confidential_policy)
+    }
+
+    pub fn delegate_surcharge_for(&self, amount: u64) -> u64 {
+        fees::mul_div(
+            amount,
+            self.delegate_surcharge_bps as u64,
+            fees::BPS_DENOMINATOR,
+            self.rounding(),
+        )
+    }
+
+    // Dust transfers are not worth the compute of an accrual
+    pub fn is_below_fee_threshold(&self, amount: u64) -> bool {
+        amount < self.min_fee_amount
+    }
+
+    // Fee owed on a public transfer, accrued against the source account
+    pub fn assess(&self, inputs: &FeeInputs, mint: &Mint) -> u64 {
+        if self.is_exempt_source(&inputs.source_owner, mint) || inputs.allow_listed {
+            // Mint authority, distributor and allow-listed transfers (airdrops, initial distribution) skip royalties
+            verbose_msg!("Source owner is exempt from royalties");
+            0
+        } else if inputs.to_royalty_account {
+            verbose_msg!("Transfer into the royalty account, skipping royalties");
+            0
+        } else if inputs.from_royalty_vault {
+            verbose_msg!("Payout from the royalty vault or rewards pool, skipping royalties");
+            0
+        } else if inputs.to_exempt_program {
+            verbose_msg!("Transfer into a program-owned account, skipping royalties");
+            0
+        } else if inputs.holds_exemption_pass {
+            verbose_msg!("Exemption pass holder, skipping royalties");
+            0
+        } else if inputs.native_wrap {
+            verbose_msg!("Native wrap or unwrap, skipping royalties");
+            0
+        } else if inputs.to_exchange && self.exchange_policy() != ExchangePolicy::Standard {
+            verbose_msg!("Exchange deposit, applying exchange policy");
+            match self.exchange_policy() {
+                ExchangePolicy::FlatFee => self.exchange_flat_fee.min(inputs.amount),
+                _ => 0,
+            }
+        } else if self.is_below_fee_threshold(inputs.threshold_amount) {
+            verbose_msg!("Transfer amount below fee threshold");
+            0
+        } else if inputs.by_permanent_delegate && self.delegate_policy() == DelegatePolicy::Surcharge {
+            self.royalty_for(inputs)
+                .saturating_add(self.delegate_surcharge_for(inputs.amount))
+        } else {
+            self.royalty_for(inputs)
+        }
+    }
+
+    // Scheduled or trade rate, raised by the seller's volume tier, then adjusted by the volume
+    // controller when it is enabled and stats are tracked. Never above the locked rate once fees
+    // are final.
+    pub fn effective_fee_bps(
+        &self,
+        now: i64,
+        trade_side: TradeSide,
+        rolling_volume: Option<u64>,
+        lifetime_volume: u64,
+    ) -> u16 {
+        let base_bps = self.tiered_fee_bps(self.base_fee_bps(now, trade_side), lifetime_volume);
+        let fee_bps = match rolling_volume {
+            Some(rolling_volume) if self.dynamic_fee_enabled != 0 => fees::dynamic_fee_bps(
+                base_bps,
+                rolling_volume,
+                self.dynamic_volume_target,
+                self.dynamic_min_fee_bps,
+                self.dynamic_max_fee_bps,
+            ),
+            _ => base_bps,
+        };
+        if self.is_locked(LOCK_FEES) {
+            fee_bps.min(self.locked_fee_bps)
+        } else {
+            fee_bps
+        }
+    }
+
+    pub fn royalty_for(&self, inputs: &FeeInputs) -> u64 {
+        let fee_bps = self.effective_fee_bps(
+            inputs.now,
+            inputs.trade_side,
+            inputs.rolling_volume,
+            inputs.lifetime_volume,
+        );
+        fees::clamp_fee(
+            fees::bps_fee(inputs.amount, fee_bps, self.rounding()),
+            inputs.amount,
+            self.min_fee(),
+            self.max_fee(),
+        )
+    }
+}
+
+pub fn initialize_config(ctx: Context<InitializeConfig>, params: InitializeConfigParams) -> Result<()> {
+    ctx.accounts.config.load_init()?.init(
+        ctx.accounts.authority.key(),
+        ctx.accounts.mint.key(),
+        &params,
+        ctx.bumps.config,
+    )
+}
+
+pub fn set_distributors(ctx: Context<UpdateConfig>, distributors: Vec<Pubkey>) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.set_distributors(&distributors)
+}
+
+pub fn set_min_fee_amount(ctx: Context<UpdateConfig>, min_fee_amount: u64) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.min_fee_amount = min_fee_amount;
+
+    Ok(())
+}
+
+pub fn set_fee_bounds(
+    ctx: Context<UpdateConfig>,
+    min_fee: Option<u64>,
+    max_fee: Option<u64>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.set_fee_bounds(min_fee, max_fee)
+}
+
+pub fn set_rounding_mode(ctx: Context<UpdateConfig>, rounding: RoundingMode) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.rounding = rounding as u8;
+
+    Ok(())
+}
+
+// Recipient changes are two-step: the authority proposes, the new account's owner accepts
+pub fn propose_recipient(ctx: Context<UpdateConfig>, new_recipient: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_RECIPIENTS)?;
+    require!(
+        config.rotation_allowed(Clock::get()?.unix_timestamp),
+        TransferHookError::RotationCooldownActive
+    );
+
+    config.pending_recipient = new_recipient;
+
+    Ok(())
+}
+
+pub fn accept_recipient(ctx: Context<AcceptRecipient>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let new_recipient = ctx.accounts.new_recipient.key();
+
+    {
+        let config = &mut ctx.accounts.config.load_mut()?;
+        // A recipient proposed before finalization can't be accepted after it
+        config.require_unlocked(LOCK_RECIPIENTS)?;
+        require!(
+            config.pending_recipient != Pubkey::default(),
+            TransferHookError::NoPendingRecipient
+        );
+        require!(
+            config.pending_recipient == new_recipient,
+            TransferHookError::InvalidRoyaltyRecipient
+        );
+        require!(
+            config.rotation_allowed(now),
+            TransferHookError::RotationCooldownActive
+        );
+
+        config.royalty_recipient = new_recipient;
+        config.pending_recipient = Pubkey::default();
+        config.last_rotation_ts = now;
+    }
+
+    // Point the meta list at the new recipient so token2022 resolves it on the next transfer
+    meta_list::rewrite_from_config(
+        &ctx.accounts.config,
+        &ctx.accounts.extra_account_meta_list.to_account_info(),
+        ctx.accounts.mint.to_account_info().owner,
+    )
+}
+
+pub fn set_rotation_cooldown(ctx: Context<UpdateConfig>, cooldown_seconds: i64) -> Result<()> {
+    require!(cooldown_seconds >= 0, TransferHookError::InvalidCooldown);
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_RECIPIENTS)?;
+    config.rotation_cooldown = cooldown_seconds;
+
+    Ok(())
+}
+
+// Upgrade a config with an older layout in place: grow it to the current size, topping up rent
+// from the authority, and stamp the current version
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let config_info = ctx.accounts.config.to_account_info();
+
+    if config_info.data_len() < RoyaltyConfig::LEN {
+        let rent = Rent::get()?.minimum_balance(RoyaltyConfig::LEN);
+        let shortfall = rent.saturating_sub(config_info.lamports());
+        if shortfall > 0 {
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: config_info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        // New bytes are zeroed, which every appended field treats as its default
+        config_info.realloc(RoyaltyConfig::LEN, true)?;
+    }
+
+    // Checks the owner and discriminator now that the data is large enough to load
+    let loader = AccountLoader::<RoyaltyConfig>::try_from(&config_info)?;
+    let config = &mut loader.load_mut()?;
+    // Migration only appends defaults, so a renounced config can be migrated by anyone
+    require!(
+        config.authority == ctx.accounts.authority.key() || config.is_locked(LOCK_AUTHORITY),
+        TransferHookError::Unauthorized
+    );
+    require!(
+        config.version <= CONFIG_VERSION,
+        TransferHookError::ConfigVersionMismatch
+    );
+
+    config.version = CONFIG_VERSION;
+
+    Ok(())
+}
+
+// Upgrade a meta list written by an older version to every account the current Execute resolves.
+// Runs after `migrate_config`, the list is rebuilt from the migrated config alone so anyone may
+// pay for it.
+pub fn migrate_extra_account_meta_list(ctx: Context<MigrateExtraAccountMetaList>) -> Result<()> {
+    let account_metas = meta_list::extra_account_metas(
+        &*RoyaltyConfig::load_current(&ctx.accounts.config)?,
+        ctx.accounts.mint.to_account_info().owner,
+    )?;
+    let extra_account_meta_list = ctx.accounts.extra_account_meta_list.to_account_info();
+    meta_list::resize_extra_account_meta_list(
+        &ctx.accounts.payer.to_account_info(),
+        &extra_account_meta_list,
+        &ctx.accounts.system_program.to_account_info(),
+        account_metas.len(),
+    )?;
+    meta_list::rewrite_extra_account_metas(&extra_account_meta_list, &account_metas)
+}
+
+pub fn set_holder_state_gc(
+    ctx: Context<UpdateConfig>,
+    holder_state_ttl: i64,
+    gc_rent_destination: Pubkey,
+) -> Result<()> {
+    require!(holder_state_ttl >= 0, TransferHookError::InvalidCooldown);
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    // Collecting holder stats resets the volume fee tiers read, and the rent goes to the destination
+    config.require_unlocked(LOCK_FEES | LOCK_RECIPIENTS)?;
+    config.holder_state_ttl = holder_state_ttl;
+    config.gc_rent_destination = gc_rent_destination;
+
+    Ok(())
+}
+
+pub fn set_delegate_policy(
+    ctx: Context<UpdateConfig>,
+    policy: DelegatePolicy,
+    surcharge_bps: u16,
+) -> Result<()> {
+    require!(
+        surcharge_bps as u64 <= fees::BPS_DENOMINATOR,
+        TransferHookError::InvalidBps
+    );
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES | LOCK_RESTRICTIONS)?;
+    config.delegate_policy = policy as u8;
+    config.delegate_surcharge_bps = surcharge_bps;
+
+    Ok(())
+}
+
+pub fn set_confidential_policy(
+    ctx: Context<UpdateConfig>,
+    policy: ConfidentialPolicy,
+    flat_fee: u64,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES | LOCK_RESTRICTIONS)?;
+    config.confidential_policy = policy as u8;
+    config.confidential_flat_fee = flat_fee;
+
+    Ok(())
+}
+
+pub fn set_net_extension_fee(ctx: Context<UpdateConfig>, net_extension_fee: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.net_extension_fee = net_extension_fee as u8;
+
+    Ok(())
+}
+
+pub fn set_ui_amount_thresholds(ctx: Context<UpdateConfig>, ui_amount_thresholds: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.ui_amount_thresholds = ui_amount_thresholds as u8;
+
+    Ok(())
+}
+
+pub fn set_fee_schedule(ctx: Context<UpdateConfig>, entries: Vec<FeeScheduleEntry>) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.set_fee_schedule(&entries)
+}
+
+pub fn set_volume_tiers(ctx: Context<UpdateConfig>, entries: Vec<VolumeTierEntry>) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.set_volume_tiers(&entries)
+}
+
+pub fn set_dynamic_fee(
+    ctx: Context<UpdateConfig>,
+    enabled: bool,
+    volume_window: i64,
+    volume_target: u64,
+    min_fee_bps: u16,
+    max_fee_bps: u16,
+) -> Result<()> {
+    require!(volume_window > 0, TransferHookError::InvalidCooldown);
+    require!(
+        min_fee_bps <= max_fee_bps && max_fee_bps as u64 <= fees::BPS_DENOMINATOR,
+        TransferHookError::InvalidBps
+    );
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.dynamic_fee_enabled = enabled as u8;
+    config.dynamic_volume_window = volume_window;
+    config.dynamic_volume_target = volume_target;
+    config.dynamic_min_fee_bps = min_fee_bps;
+    config.dynamic_max_fee_bps = max_fee_bps;
+
+    Ok(())
+}
+
+pub fn set_distribution_program(
+    ctx: Context<UpdateConfig>,
+    distribution_program: Pubkey,
+    adapter: DistributionAdapterKind,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_RECIPIENTS)?;
+    config.distribution_program = distribution_program;
+    config.distribution_adapter = adapter as u8;
+
+    Ok(())
+}
+
+pub fn set_trade_fees(
+    ctx: Context<UpdateConfig>,
+    enabled: bool,
+    buy_fee_bps: u16,
+    sell_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        buy_fee_bps as u64 <= fees::BPS_DENOMINATOR && sell_fee_bps as u64 <= fees::BPS_DENOMINATOR,
+        TransferHookError::InvalidBps
+    );
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.trade_fees_enabled = enabled as u8;
+    config.buy_fee_bps = buy_fee_bps;
+    config.sell_fee_bps = sell_fee_bps;
+
+    Ok(())
+}
+
+pub fn set_native_wrap_exempt(ctx: Context<UpdateConfig>, exempt: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.native_wrap_exempt = exempt as u8;
+
+    Ok(())
+}
+
+// Permanently locks the `LOCK_*` groups in `fields`. Locks only ever accumulate, and locking fees
+// pins the rate in force now as the most the hook will ever charge.
+pub fn finalize_config(ctx: Context<UpdateConfig>, fields: u16) -> Result<()> {
+    require!(
+        fields != 0 && fields & !LOCK_ALL == 0,
+        TransferHookError::InvalidLockFields
+    );
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.lock(fields, Clock::get()?.unix_timestamp);
+
+    msg!("Config finalized, locked fields {:#06b}", config.locked_fields);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    // Only the mint authority can create the config so it can't be front-run
+    #[account(
+        mut,
+        constraint = mint.mint_authority == COption::Some(authority.key()) @ TransferHookError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = RoyaltyConfig::LEN,
+        seeds = [CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+}
+
+// For config changes that also change how extra accounts are resolved
+#[derive(Accounts)]
+pub struct UpdateConfigWithMetaList<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [META_LIST_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: may hold an older, shorter layout, validated in the handler after realloc
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [META_LIST_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptRecipient<'info> {
+    // Owner of the proposed token account must co-sign the rotation
+    pub recipient_owner: Signer<'info>,
+    #[account(
+        token::mint = mint,
+        token::authority = recipient_owner,
+    )]
+    pub new_recipient: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [META_LIST_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+}
@@ -0,0 +1,257 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::{self, instructions::load_current_index_checked};
+use anchor_spl::{
+    token_2022::spl_token_2022::instruction::transfer_checked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::accounting::{self, VaultFlow, ROYALTY_ACCOUNTING_SEED};
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+use crate::extensions;
+use crate::outflow::{self, Outflow};
+use crate::settlement::{self, FEE_ACCRUAL_SEED};
+use crate::validation;
+use crate::vault::VAULT_AUTHORITY_SEED;
+
+pub const FEE_CREDIT_SEED: &[u8] = b"fee-credit";
+
+// Royalties an owner prepaid into the vault. The hook draws fees from the balance before charging
+// the transfer, and the unused balance can be refunded.
+#[account(zero_copy)]
+pub struct FeeCredit {
+    pub balance: u64,
+    pub total_deposited: u64,
+    pub total_drawn: u64,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl FeeCredit {
+    pub const LEN: usize = 8 + std::mem::size_of::<FeeCredit>();
+}
+
+// The hook receives the source owner's credit PDA whether or not it has been created. Returns
+// what is left of `fee` after drawing from the balance.
+pub fn draw_if_initialized(fee_credit: &AccountInfo, mint: &Pubkey, owner: &Pubkey, fee: u64) -> Result<u64> {
+    if fee == 0 || fee_credit.owner != &crate::ID || fee_credit.data_len() != FeeCredit::LEN {
+        return Ok(fee);
+    }
+
+    let loader = AccountLoader::<FeeCredit>::try_from(fee_credit)?;
+    let credit = &mut loader.load_mut()?;
+    validation::require_derived(
+        fee_credit,
+        &[FEE_CREDIT_SEED, mint.as_ref(), owner.as_ref()],
+        credit.bump,
+    )?;
+    let drawn = fee.min(credit.balance);
+    credit.balance -= drawn;
+    credit.total_drawn = credit.total_drawn.saturating_add(drawn);
+
+    Ok(fee - drawn)
+}
+
+pub fn initialize_fee_credit(ctx: Context<InitializeFeeCredit>) -> Result<()> {
+    let credit = &mut ctx.accounts.fee_credit.load_init()?;
+    credit.owner = ctx.accounts.owner.key();
+    credit.mint = ctx.accounts.mint.key();
+    credit.bump = ctx.bumps.fee_credit;
+
+    Ok(())
+}
+
+// Credits what the vault receives from the owner's transfer_checked of `amount`, which has to be
+// the next instruction in the transaction. The program can't make the transfer itself, the hook
+// would re-enter it. Transfers into the vault aren't charged, but one from an account that owes
+// royalties would settle them, so those have to be paid first.
+pub fn deposit_credits<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositCredits<'info>>,
+    amount: u64,
+) -> Result<()> {
+    require!(amount > 0, TransferHookError::InvalidFeeCredit);
+    require!(
+        settlement::owed_if_initialized(&ctx.accounts.fee_accrual.to_account_info())? == 0,
+        TransferHookError::RoyaltiesUnsettled
+    );
+
+    let instructions = ctx.accounts.instructions.to_account_info();
+    let current = load_current_index_checked(&instructions)? as usize;
+    let expected = transfer_checked(
+        ctx.accounts.token_program.key,
+        &ctx.accounts.owner_token_account.key(),
+        &ctx.accounts.mint.key(),
+        &ctx.accounts.royalty_vault.key(),
+        ctx.accounts.owner.key,
+        &[],
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+    outflow::require_following_transfer(&instructions, current + 1, &expected)?;
+
+    // The transfer-fee extension withholds its fee in the vault, out of reach
+    let received = amount.saturating_sub(extensions::transfer_fee_extension_fee(
+        &ctx.accounts.mint.to_account_info(),
+        amount,
+        Clock::get()?.epoch,
+    )?);
+    accounting::record_vault_flow_if_initialized(
+        &ctx.accounts.royalty_accounting.to_account_info(),
+        &ctx.accounts.mint.key(),
+        VaultFlow::Deposit,
+        received,
+    )?;
+
+    let credit = &mut ctx.accounts.fee_credit.load_mut()?;
+    credit.balance = credit.balance.saturating_add(received);
+    credit.total_deposited = credit.total_deposited.saturating_add(received);
+
+    msg!("Deposited {} fee credits, balance {}", received, credit.balance);
+
+    Ok(())
+}
+
+// Approves the owner to take unused credits back out of the vault, the owner's transfer has to
+// follow in the same transaction. Other vault outflows don't reserve credit balances, so a refund
+// can fail until the vault is topped up again.
+pub fn refund_credits<'info>(
+    ctx: Context<'_, '_, '_, 'info, RefundCredits<'info>>,
+    amount: u64,
+) -> Result<()> {
+    {
+        let credit = &mut ctx.accounts.fee_credit.load_mut()?;
+        require!(
+            amount > 0 && amount <= credit.balance,
+            TransferHookError::InvalidFeeCredit
+        );
+        credit.balance -= amount;
+    }
+    require!(
+        amount <= ctx.accounts.royalty_vault.amount,
+        TransferHookError::InsufficientVaultBalance
+    );
+
+    let mint_key = ctx.accounts.mint.key();
+    let bump = ctx.accounts.config.load()?.vault_authority_bump;
+    Outflow {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        source: &ctx.accounts.royalty_vault.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        decimals: ctx.accounts.mint.decimals,
+        owner: &ctx.accounts.vault_authority.to_account_info(),
+        delegate: &ctx.accounts.owner.to_account_info(),
+        instructions: &ctx.accounts.instructions.to_account_info(),
+    }
+    .approve(
+        &[(ctx.accounts.owner_token_account.key(), amount)],
+        &[&[VAULT_AUTHORITY_SEED, mint_key.as_ref(), &[bump]]],
+    )?;
+    accounting::record_vault_flow_if_initialized(
+        &ctx.accounts.royalty_accounting.to_account_info(),
+        &mint_key,
+        VaultFlow::Withdrawal,
+        amount,
+    )?;
+
+    msg!("Refunded {} fee credits", amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeCredit<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = owner,
+        space = FeeCredit::LEN,
+        seeds = [FEE_CREDIT_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub fee_credit: AccountLoader<'info, FeeCredit>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositCredits<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [FEE_CREDIT_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump = fee_credit.load()?.bump,
+    )]
+    pub fee_credit: AccountLoader<'info, FeeCredit>,
+    #[account(
+        token::mint = mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: fee accrual PDA of the owner's token account, read when initialized
+    #[account(
+        seeds = [FEE_ACCRUAL_SEED, owner_token_account.key().as_ref()],
+        bump,
+    )]
+    pub fee_accrual: UncheckedAccount<'info>,
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, the owner's transfer is checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RefundCredits<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [FEE_CREDIT_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump = fee_credit.load()?.bump,
+    )]
+    pub fee_credit: AccountLoader<'info, FeeCredit>,
+    /// CHECK: PDA owning the royalty vault, signs the approval
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = config.load()?.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        token::mint = mint,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: instructions sysvar, the owner's transfer is checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
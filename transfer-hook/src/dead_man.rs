@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+use crate::config::{RoyaltyConfig, UpdateConfig, LOCK_ALL, LOCK_RESTRICTIONS};
+use crate::enforcement::{RULE_ACCOUNT_LOCK, RULE_DENY_LIST};
+use crate::error::TransferHookError;
+
+// What happens to a config whose authority has been inactive for `dead_man_epochs`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DeadManAction {
+    // Account locks and the deny list drop to monitor-only and restrictions are locked, so nobody
+    // stays blocked by the authority. Rules that protect holders keep blocking, fees and recipients
+    // stay with the authority.
+    Relax,
+    // Everything is locked and the authority renounced, as with `finalize_config(LOCK_ALL)`.
+    // Account locks and deny list entries stop being enforced with it.
+    Freeze,
+}
+
+impl DeadManAction {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => DeadManAction::Freeze,
+            _ => DeadManAction::Relax,
+        }
+    }
+}
+
+impl RoyaltyConfig {
+    pub fn dead_man_tripped(&self, epoch: u64) -> bool {
+        self.dead_man_epochs != 0 && epoch >= self.last_admin_epoch.saturating_add(self.dead_man_epochs)
+    }
+
+    // Account constraint of every admin context: checks the signer and restarts the switch.
+    // Once it has tripped the authority can't act until `trigger_dead_man_switch` has run.
+    pub fn record_admin_action(loader: &AccountLoader<RoyaltyConfig>, authority: &Pubkey) -> Result<bool> {
+        if RoyaltyConfig::load_current(loader)?.authority != *authority {
+            return Ok(false);
+        }
+
+        let config = &mut loader.load_mut()?;
+        let epoch = Clock::get()?.epoch;
+        require!(
+            !config.dead_man_tripped(epoch),
+            TransferHookError::DeadManSwitchTripped
+        );
+        config.last_admin_epoch = epoch;
+
+        Ok(true)
+    }
+}
+
+// Zero `epochs` disables the switch
+pub fn set_dead_man_switch(ctx: Context<UpdateConfig>, epochs: u64, action: DeadManAction) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    // Either action changes what is restricted, so the switch is final once restrictions are
+    config.require_unlocked(LOCK_RESTRICTIONS)?;
+    config.dead_man_epochs = epochs;
+    config.dead_man_action = action as u8;
+
+    msg!("Dead-man switch set to {} epochs", epochs);
+
+    Ok(())
+}
+
+// Proof of life, the admin context already restarted the switch
+pub fn check_in(_ctx: Context<UpdateConfig>) -> Result<()> {
+    Ok(())
+}
+
+// Permissionless once the authority has been inactive long enough. Applies the configured action
+// for good and disarms the switch.
+pub fn trigger_dead_man_switch(ctx: Context<TriggerDeadManSwitch>) -> Result<()> {
+    // Stale layouts must be migrated before their fields mean anything
+    drop(RoyaltyConfig::load_current(&ctx.accounts.config)?);
+    let config = &mut ctx.accounts.config.load_mut()?;
+    require!(
+        config.dead_man_tripped(Clock::get()?.epoch),
+        TransferHookError::DeadManSwitchNotTripped
+    );
+
+    match DeadManAction::from_u8(config.dead_man_action) {
+        DeadManAction::Relax => {
+            config.monitor_only_rules |= RULE_ACCOUNT_LOCK | RULE_DENY_LIST;
+            config.lock(LOCK_RESTRICTIONS, Clock::get()?.unix_timestamp);
+        }
+        DeadManAction::Freeze => config.lock(LOCK_ALL, Clock::get()?.unix_timestamp),
+    }
+    config.dead_man_epochs = 0;
+
+    msg!("Dead-man switch triggered, locked fields {:#06b}", config.locked_fields);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TriggerDeadManSwitch<'info> {
+    #[account(mut)]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+}
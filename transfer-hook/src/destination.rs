@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::solana_zk_token_sdk::curve25519::edwards::{
+    validate_edwards, PodEdwardsPoint,
+};
+
+use crate::config::{RoyaltyConfig, UpdateConfig, LOCK_FEES};
+use crate::error::TransferHookError;
+
+// Overrides for destinations owned by a program PDA, as bits of `program_destination_overrides`.
+// Vaults, escrows and lending pools hold tokens for many users, so owner-centric treatment
+// doesn't fit them.
+// Transfers into them aren't charged
+pub const PROGRAM_DESTINATION_FEE_EXEMPT: u8 = 1 << 0;
+// They don't earn raffle tickets
+pub const PROGRAM_DESTINATION_NO_RAFFLE: u8 = 1 << 1;
+pub const ALL_PROGRAM_DESTINATION_OVERRIDES: u8 = PROGRAM_DESTINATION_FEE_EXEMPT | PROGRAM_DESTINATION_NO_RAFFLE;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DestinationClass {
+    // Owner key is a point on the curve, i.e. a wallet with a private key
+    Wallet,
+    // Owner key is off the curve, which only PDAs are
+    Program,
+}
+
+// Checked with the curve syscall, the owner's account itself isn't among the hook's accounts
+pub fn classify(owner: &Pubkey) -> DestinationClass {
+    if validate_edwards(&PodEdwardsPoint(owner.to_bytes())) {
+        DestinationClass::Wallet
+    } else {
+        DestinationClass::Program
+    }
+}
+
+impl RoyaltyConfig {
+    // Classifies the destination only when an override could apply
+    pub fn program_destination_override(&self, owner: &Pubkey, flag: u8) -> bool {
+        self.program_destination_overrides & flag != 0 && classify(owner) == DestinationClass::Program
+    }
+}
+
+pub fn set_program_destination_policy(ctx: Context<UpdateConfig>, overrides: u8) -> Result<()> {
+    require!(
+        overrides & !ALL_PROGRAM_DESTINATION_OVERRIDES == 0,
+        TransferHookError::InvalidDestinationPolicy
+    );
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.program_destination_overrides = overrides;
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use spl_discriminator::SplDiscriminate;
+use spl_transfer_hook_interface::instruction::{ExecuteInstruction, InitializeExtraAccountMetaListInstruction};
+
+use crate::error::TransferHookError;
+
+// Our own instructions outside Anchor's namespace: [AUX_DISCRIMINATOR, version, opcode, args..].
+// The version byte lets the envelope change without touching deployed callers of older versions.
+pub const AUX_DISCRIMINATOR: [u8; 8] = *b"thookaux";
+pub const AUX_VERSION: u8 = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AuxOp {
+    // Same as `get_state`, under an opcode that doesn't depend on the instruction's name
+    GetState = 0,
+}
+
+impl AuxOp {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(AuxOp::GetState),
+            _ => None,
+        }
+    }
+}
+
+// Routes instructions Anchor doesn't recognise. Interface instructions are matched on their SPL
+// discriminators, trailing bytes a later interface revision may append are ignored. Malformed data
+// and well-formed instructions we don't implement fail with different errors.
+pub fn dispatch<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+    if data.len() < 8 {
+        return err!(TransferHookError::MalformedInstruction);
+    }
+    let (discriminator, rest) = data.split_at(8);
+
+    if discriminator == ExecuteInstruction::SPL_DISCRIMINATOR_SLICE {
+        // token2022 CPIs this on every transfer
+        let amount_bytes = rest.get(..8).ok_or(TransferHookError::MalformedInstruction)?;
+        crate::__private::__global::transfer_hook(program_id, accounts, amount_bytes)
+    } else if discriminator == InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE {
+        // The meta list is derived from config, see `initialize_extra_account_meta_list`
+        err!(TransferHookError::UnsupportedInstruction)
+    } else if discriminator == AUX_DISCRIMINATOR {
+        dispatch_aux(program_id, accounts, rest)
+    } else {
+        err!(TransferHookError::UnsupportedInstruction)
+    }
+}
+
+fn dispatch_aux<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+    let (&version, rest) = data.split_first().ok_or(TransferHookError::MalformedInstruction)?;
+    require!(version == AUX_VERSION, TransferHookError::UnsupportedAuxVersion);
+    let (&opcode, args) = rest.split_first().ok_or(TransferHookError::MalformedInstruction)?;
+
+    match AuxOp::from_u8(opcode).ok_or(TransferHookError::UnsupportedInstruction)? {
+        AuxOp::GetState => crate::__private::__global::get_state(program_id, accounts, args),
+    }
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hash, instruction::Instruction};
+
+use super::{DistributionAdapter, ForwardAccounts};
+
+// Targets Anchor-based revenue-sharing programs exposing
+// `deposit(amount: u64)` with accounts (source, source_authority, mint, token_program, ...rest).
+// The rest are passed through from the remaining accounts with their original flags.
+pub struct AnchorDepositAdapter;
+
+impl DistributionAdapter for AnchorDepositAdapter {
+    fn forward_instruction(
+        distribution_program: &Pubkey,
+        accounts: &ForwardAccounts,
+        remaining_accounts: &[AccountInfo],
+        amount: u64,
+    ) -> Result<Instruction> {
+        let mut data = hash(b"global:deposit").to_bytes()[..8].to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+
+        let mut account_metas = vec![
+            AccountMeta::new(*accounts.vault.key, false),
+            AccountMeta::new_readonly(*accounts.vault_authority.key, true),
+            AccountMeta::new_readonly(*accounts.mint.key, false),
+            AccountMeta::new_readonly(*accounts.token_program.key, false),
+        ];
+        account_metas.extend(remaining_accounts.iter().map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }));
+
+        Ok(Instruction {
+            program_id: *distribution_program,
+            accounts: account_metas,
+            data,
+        })
+    }
+}
@@ -0,0 +1,189 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::accounting::{self, VaultFlow, ROYALTY_ACCOUNTING_SEED};
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+use crate::ledger::CREATOR_LEDGER_SEED;
+use crate::rewards::REWARDS_POOL_SEED;
+use crate::vault::{self, VAULT_AUTHORITY_SEED};
+
+#[cfg(feature = "anchor-deposit-adapter")]
+pub mod anchor_deposit;
+
+// Accounts every adapter can rely on, the vault authority PDA signs the CPI
+pub struct ForwardAccounts<'a, 'info> {
+    pub vault: &'a AccountInfo<'info>,
+    pub vault_authority: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+// Builds the CPI into a downstream distribution program. Each supported program gets an adapter
+// behind its own Cargo feature.
+pub trait DistributionAdapter {
+    fn forward_instruction(
+        distribution_program: &Pubkey,
+        accounts: &ForwardAccounts,
+        remaining_accounts: &[AccountInfo],
+        amount: u64,
+    ) -> Result<Instruction>;
+}
+
+// Which adapter `forward_royalties` builds the CPI with, pinned in config next to the program.
+// A build without the adapter's feature rejects the forward.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DistributionAdapterKind {
+    AnchorDeposit,
+}
+
+impl DistributionAdapterKind {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(DistributionAdapterKind::AnchorDeposit),
+            _ => None,
+        }
+    }
+}
+
+impl RoyaltyConfig {
+    pub fn distribution_adapter(&self) -> Option<DistributionAdapterKind> {
+        DistributionAdapterKind::from_u8(self.distribution_adapter)
+    }
+}
+
+// Permissionless: the destination program is pinned in config, and the amount can only come from
+// the vault balance not owed to creators or holder rewards
+pub fn forward_royalties<'info>(
+    ctx: Context<'_, '_, '_, 'info, ForwardRoyalties<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let reserved = vault::reserved_balance(
+        &ctx.accounts.creator_ledger.to_account_info(),
+        &ctx.accounts.rewards_pool.to_account_info(),
+    )?;
+    require!(
+        amount <= ctx.accounts.royalty_vault.amount.saturating_sub(reserved),
+        TransferHookError::InsufficientVaultBalance
+    );
+
+    let (adapter, bump) = {
+        let config = ctx.accounts.config.load()?;
+        (config.distribution_adapter(), config.vault_authority_bump)
+    };
+    let instruction = forward_instruction(&ctx, adapter, amount)?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, mint_key.as_ref(), &[bump]]];
+
+    let mut account_infos = vec![
+        ctx.accounts.royalty_vault.to_account_info(),
+        ctx.accounts.vault_authority.to_account_info(),
+        ctx.accounts.mint.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.distribution_program.to_account_info(),
+    ];
+    account_infos.extend_from_slice(ctx.remaining_accounts);
+    let vault_before = ctx.accounts.royalty_vault.amount;
+    invoke_signed(&instruction, &account_infos, signer_seeds)?;
+
+    // The downstream program decides what it pulls, so account for what left the vault and hold it
+    // to the amount that was checked
+    ctx.accounts.royalty_vault.reload()?;
+    let forwarded = vault_before.saturating_sub(ctx.accounts.royalty_vault.amount);
+    require!(forwarded <= amount, TransferHookError::InsufficientVaultBalance);
+    accounting::record_vault_flow_if_initialized(
+        &ctx.accounts.royalty_accounting.to_account_info(),
+        &mint_key,
+        VaultFlow::Withdrawal,
+        forwarded,
+    )?;
+
+    msg!("Forwarded {} royalty tokens for distribution", amount);
+
+    Ok(())
+}
+
+// Unused in builds without any adapter feature
+#[allow(dead_code)]
+fn forward_instruction_with<'info, A: DistributionAdapter>(
+    ctx: &Context<'_, '_, '_, 'info, ForwardRoyalties<'info>>,
+    amount: u64,
+) -> Result<Instruction> {
+    let vault = ctx.accounts.royalty_vault.to_account_info();
+    let vault_authority = ctx.accounts.vault_authority.to_account_info();
+    let mint = ctx.accounts.mint.to_account_info();
+    let token_program = ctx.accounts.token_program.to_account_info();
+
+    A::forward_instruction(
+        ctx.accounts.distribution_program.key,
+        &ForwardAccounts {
+            vault: &vault,
+            vault_authority: &vault_authority,
+            mint: &mint,
+            token_program: &token_program,
+        },
+        ctx.remaining_accounts,
+        amount,
+    )
+}
+
+// CPI built by the adapter configured for the program, when this build includes it
+#[allow(unused_variables, unreachable_patterns)]
+fn forward_instruction<'info>(
+    ctx: &Context<'_, '_, '_, 'info, ForwardRoyalties<'info>>,
+    adapter: Option<DistributionAdapterKind>,
+    amount: u64,
+) -> Result<Instruction> {
+    match adapter {
+        #[cfg(feature = "anchor-deposit-adapter")]
+        Some(DistributionAdapterKind::AnchorDeposit) => {
+            forward_instruction_with::<anchor_deposit::AnchorDepositAdapter>(ctx, amount)
+        }
+        _ => err!(TransferHookError::NoDistributionAdapter),
+    }
+}
+
+#[derive(Accounts)]
+pub struct ForwardRoyalties<'info> {
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.mint == mint.key(),
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+        constraint = RoyaltyConfig::load_current(&config)?.distribution_program == distribution_program.key() @ TransferHookError::InvalidDistributionProgram,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the royalty vault, signs the CPI
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = config.load()?.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: downstream distribution program pinned in config
+    #[account(executable)]
+    pub distribution_program: UncheckedAccount<'info>,
+    /// CHECK: creator ledger PDA, read when initialized so unclaimed balances aren't forwarded
+    #[account(
+        seeds = [CREATOR_LEDGER_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub creator_ledger: UncheckedAccount<'info>,
+    /// CHECK: rewards pool PDA, read when initialized so unfunded rewards aren't forwarded
+    #[account(
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub rewards_pool: UncheckedAccount<'info>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
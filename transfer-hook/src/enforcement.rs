@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::config::{RoyaltyConfig, UpdateConfig, LOCK_RESTRICTIONS};
+use crate::error::TransferHookError;
+
+// Rules `transfer_hook` can reject a transfer for, as bits of `monitor_only_rules`
+pub const RULE_ACCOUNT_LOCK: u16 = 1 << 0;
+pub const RULE_DELEGATE_BLOCK: u16 = 1 << 1;
+pub const RULE_CONFIDENTIAL_BLOCK: u16 = 1 << 2;
+pub const RULE_STAKE_LOCK: u16 = 1 << 3;
+pub const RULE_DENY_LIST: u16 = 1 << 4;
+pub const ALL_RULES: u16 =
+    RULE_ACCOUNT_LOCK | RULE_DELEGATE_BLOCK | RULE_CONFIDENTIAL_BLOCK | RULE_STAKE_LOCK | RULE_DENY_LIST;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Enforcement {
+    // Violations reject the transfer
+    Enforce,
+    // Violations are only reported through `RuleViolation`, for trialling rules in production
+    MonitorOnly,
+}
+
+// Emitted instead of failing when a monitor-only rule is violated
+#[event]
+pub struct RuleViolation {
+    pub rule: u16,
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+impl RoyaltyConfig {
+    pub fn enforcement(&self, rule: u16) -> Enforcement {
+        if self.monitor_only_rules & rule != 0 {
+            Enforcement::MonitorOnly
+        } else {
+            Enforcement::Enforce
+        }
+    }
+
+    // Ok when `violated` is false. A violated rule fails with `error` when enforced, and emits
+    // `transfer` tagged with the rule and lets the transfer through when monitor-only.
+    pub fn check_rule(
+        &self,
+        rule: u16,
+        violated: bool,
+        error: TransferHookError,
+        transfer: &RuleViolation,
+    ) -> Result<()> {
+        if !violated {
+            return Ok(());
+        }
+        match self.enforcement(rule) {
+            Enforcement::Enforce => Err(error.into()),
+            Enforcement::MonitorOnly => {
+                emit!(RuleViolation { rule, ..*transfer });
+                Ok(())
+            }
+        }
+    }
+}
+
+// Relaxing rules to monitor-only is a restriction change, so it is frozen with them
+pub fn set_rule_enforcement(ctx: Context<UpdateConfig>, rules: u16, enforcement: Enforcement) -> Result<()> {
+    require!(
+        rules != 0 && rules & !ALL_RULES == 0,
+        TransferHookError::InvalidRules
+    );
+
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_RESTRICTIONS)?;
+    match enforcement {
+        Enforcement::Enforce => config.monitor_only_rules &= !rules,
+        Enforcement::MonitorOnly => config.monitor_only_rules |= rules,
+    }
+
+    msg!("Monitor-only rules {:#06b}", config.monitor_only_rules);
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum TransferHookError {
+    #[msg("Signer is not the config authority")]
+    Unauthorized,
+    #[msg("Too many distributor accounts")]
+    TooManyDistributors,
+    #[msg("Minimum fee must not exceed maximum fee")]
+    InvalidFeeBounds,
+    #[msg("Royalty account does not match the configured recipient")]
+    InvalidRoyaltyRecipient,
+    #[msg("No recipient rotation is pending")]
+    NoPendingRecipient,
+    #[msg("Recipient rotation cooldown has not elapsed")]
+    RotationCooldownActive,
+    #[msg("Duration must not be negative")]
+    InvalidCooldown,
+    #[msg("Config account layout is not the current version, run migrate_config")]
+    ConfigVersionMismatch,
+    #[msg("Holder state collection is disabled for this mint")]
+    HolderStateGcDisabled,
+    #[msg("Holder state still has a balance or recent activity")]
+    HolderStateNotStale,
+    #[msg("Rent destination does not match config")]
+    InvalidRentDestination,
+    #[msg("Holder state does not belong to this owner and mint")]
+    InvalidHolderState,
+    #[msg("Transfer log account is malformed or belongs to another mint")]
+    InvalidTransferLog,
+    #[msg("Transfer log capacity out of range")]
+    InvalidTransferLogCapacity,
+    #[msg("Basis points must not exceed 10000")]
+    InvalidBps,
+    #[msg("Transfers by the permanent delegate are blocked for this mint")]
+    DelegateTransferBlocked,
+    #[msg("Confidential transfers are blocked for this mint")]
+    ConfidentialTransferBlocked,
+    #[msg("Destination token account is for a different mint")]
+    DestinationMintMismatch,
+    #[msg("Destination token account is frozen")]
+    DestinationFrozen,
+    #[msg("Royalty token account is for a different mint")]
+    RoyaltyAccountMintMismatch,
+    #[msg("Royalty token account is frozen")]
+    RoyaltyAccountFrozen,
+    #[msg("Source token account is locked")]
+    SourceAccountLocked,
+    #[msg("Fee schedule must be sorted by start time and fit the config")]
+    InvalidFeeSchedule,
+    #[msg("Mint stats account belongs to another mint")]
+    InvalidMintStats,
+    #[msg("Vault balance is insufficient")]
+    InsufficientVaultBalance,
+    #[msg("Distribution program does not match config")]
+    InvalidDistributionProgram,
+    #[msg("Program was built without the configured distribution adapter")]
+    NoDistributionAdapter,
+    #[msg("Stake record is shorter than the configured schema")]
+    InvalidStakeRecord,
+    #[msg("Stake record seed prefix must be 1 to 24 bytes")]
+    InvalidStakeSchema,
+    #[msg("Transfer would leave less than the staked amount")]
+    StakedBalanceLocked,
+    #[msg("Governance or realm is not an SPL Governance account, or the governance is of another realm")]
+    InvalidGovernance,
+    #[msg("Balance is not available for this snapshot")]
+    SnapshotUnavailable,
+    #[msg("Rewards pool belongs to another mint or has nothing to distribute to")]
+    InvalidRewardsPool,
+    #[msg("Raffle belongs to another mint, has no entries, or too many winners were requested")]
+    InvalidRaffle,
+    #[msg("Receipt account belongs to another mint or is not the current page")]
+    InvalidReceiptAccount,
+    #[msg("Receipt page is not full or still inside the archive window")]
+    ReceiptPageNotArchivable,
+    #[msg("Exemption pass token program must be SPL Token or Token-2022")]
+    InvalidExemptionPass,
+    #[msg("Volume tiers must be sorted by threshold and fit the config")]
+    InvalidVolumeTiers,
+    #[msg("Withdrawal policy needs 1 to 8 distinct approvers and a threshold they can meet")]
+    InvalidWithdrawalPolicy,
+    #[msg("Withdrawal policy changed since this withdrawal was proposed")]
+    WithdrawalPolicyChanged,
+    #[msg("Approver already approved this withdrawal")]
+    WithdrawalAlreadyApproved,
+    #[msg("Withdrawal lacks approvals or its timelock has not elapsed")]
+    WithdrawalNotReady,
+    #[msg("Wrapper accounts don't match, or the hooked mint isn't minted by the wrapper with matching decimals")]
+    InvalidWrapper,
+    #[msg("Legacy vault holds less than the wrapped supply")]
+    WrapperUndercollateralized,
+    #[msg("Rent sponsor balance is insufficient")]
+    InsufficientSponsorBalance,
+    #[msg("Payout recipients must be 1 to 8 accounts with weights adding up to 10000, passed in order")]
+    InvalidPayoutSplit,
+    #[msg("Distribution interval has not elapsed")]
+    PayoutNotDue,
+    #[msg("Creator ledger belongs to another mint, or creators are duplicated or weigh over 10000")]
+    InvalidCreatorLedger,
+    #[msg("Creator still has unclaimed royalties")]
+    CreatorHasUnclaimed,
+    #[msg("Token account is the royalty vault or isn't owned by the given program PDA")]
+    InvalidRescueSource,
+    #[msg("Rescue amount exceeds the balance the program doesn't account for")]
+    RescueExceedsSurplus,
+    #[msg("Rescue timelock has not elapsed")]
+    RescueNotReady,
+    #[msg("Setting has been finalized and can no longer change")]
+    ConfigLocked,
+    #[msg("Lock fields must be a non-empty combination of the LOCK_* bits")]
+    InvalidLockFields,
+    #[msg("Exchange deposit batch must have 1 to 16 owners, each passed with its deposit PDA in order")]
+    InvalidExchangeDeposit,
+    #[msg("Pool isn't owned by a supported AMM or doesn't hold this token account as a reserve")]
+    InvalidAmmPool,
+    #[msg("Rules must be a non-empty combination of the RULE_* bits")]
+    InvalidRules,
+    #[msg("Instruction data is too short or its arguments don't decode")]
+    MalformedInstruction,
+    #[msg("Instruction is well-formed but not implemented by this program")]
+    UnsupportedInstruction,
+    #[msg("Auxiliary instruction version is not supported")]
+    UnsupportedAuxVersion,
+    #[msg("Extra account doesn't match the address derived from this transfer")]
+    UnexpectedExtraAccount,
+    #[msg("Amount bucket is out of range")]
+    InvalidAmountBucket,
+    #[msg("Meta list address holds data that isn't this program's")]
+    MetaListForeignData,
+    #[msg("Meta list is already initialized with different accounts, rewrite it through the config setters")]
+    MetaListMismatch,
+    #[msg("Execute must be invoked by token2022, not as a top-level instruction")]
+    HookCalledDirectly,
+    #[msg("Credit amount must be non-zero and refunds can't exceed the balance")]
+    InvalidFeeCredit,
+    #[msg("Authority has been inactive past the dead-man switch, trigger it first")]
+    DeadManSwitchTripped,
+    #[msg("Dead-man switch is disabled or hasn't tripped yet")]
+    DeadManSwitchNotTripped,
+    #[msg("Owner list page is invalid, or owners are missing, too many or belong to another page")]
+    InvalidOwnerListPage,
+    #[msg("Owner list page is full, resize it first")]
+    OwnerListPageFull,
+    #[msg("Source owner is on the deny list")]
+    SourceOwnerDenied,
+    #[msg("Fee accrual belongs to another mint or token account")]
+    InvalidFeeAccrual,
+    #[msg("Token account owes no accrued royalties")]
+    NothingToSettle,
+    #[msg("Overrides must be a combination of the PROGRAM_DESTINATION_* bits")]
+    InvalidDestinationPolicy,
+    #[msg("Royalty accounting belongs to another mint")]
+    InvalidRoyaltyAccounting,
+    #[msg("Royalty accounting invariant violated")]
+    AccountingInvariantViolated,
+    #[msg("Source account isn't transferring, the hook can only run inside a token2022 transfer")]
+    NotTransferring,
+    #[msg("Creator weights plus the rewards share exceed 10000 bps")]
+    RoyaltySplitExceedsTotal,
+    #[msg("Royalty token account's owner can be reassigned, it needs the immutable owner extension")]
+    RoyaltyAccountOwnerMutable,
+    #[msg("Royalty is charged on this transfer, initialize a fee accrual for the source account first")]
+    FeeAccrualRequired,
+    #[msg("Source account owes royalties, pay them to the royalty recipient first")]
+    RoyaltiesUnsettled,
+    #[msg("Royalties owed would exceed the source account's remaining balance")]
+    RoyaltiesExceedBalance,
+    #[msg("Payout has to be followed by the delegate's transfer_checked for each leg")]
+    OutflowTransferMissing,
+}
@@ -0,0 +1,186 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{create_account, CreateAccount};
+use anchor_spl::token_interface::Mint;
+
+use crate::config::{RoyaltyConfig, UpdateConfig, LOCK_FEES};
+use crate::error::TransferHookError;
+use crate::validation;
+
+pub const EXCHANGE_DEPOSIT_SEED: &[u8] = b"exchange-deposit";
+
+// Upper bound on owners registered per `register_exchange_deposits` call
+pub const MAX_EXCHANGE_BATCH: usize = 16;
+
+// How transfers into a registered exchange deposit owner are charged. Deposit flows are shared by
+// many users, so per-holder rules don't describe them well.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ExchangePolicy {
+    // Charged like any other transfer
+    Standard,
+    // Charged `exchange_flat_fee` regardless of the amount
+    FlatFee,
+    Exempt,
+}
+
+impl ExchangePolicy {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ExchangePolicy::FlatFee,
+            2 => ExchangePolicy::Exempt,
+            _ => ExchangePolicy::Standard,
+        }
+    }
+}
+
+impl RoyaltyConfig {
+    pub fn exchange_policy(&self) -> ExchangePolicy {
+        ExchangePolicy::from_u8(self.exchange_policy)
+    }
+}
+
+// Existence of this PDA marks `owner` as an exchange deposit owner for the mint. Zero-copy since
+// the hook reads it for every destination owner.
+#[account(zero_copy)]
+pub struct ExchangeDeposit {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub registered_at: i64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl ExchangeDeposit {
+    pub const LEN: usize = 8 + std::mem::size_of::<ExchangeDeposit>();
+
+    // The hook receives the PDA of every destination owner, registered owners are the ones where
+    // it exists and is ours. An existing deposit must derive from `mint` and `owner`.
+    pub fn is_registered(exchange_deposit: &AccountInfo, mint: &Pubkey, owner: &Pubkey) -> Result<bool> {
+        if exchange_deposit.owner != &crate::ID || exchange_deposit.data_len() != ExchangeDeposit::LEN {
+            return Ok(false);
+        }
+        let bump = {
+            let data = exchange_deposit.try_borrow_data()?;
+            if data[..8] != ExchangeDeposit::DISCRIMINATOR {
+                return Ok(false);
+            }
+            bytemuck::from_bytes::<ExchangeDeposit>(&data[8..]).bump
+        };
+        validation::require_derived(
+            exchange_deposit,
+            &[EXCHANGE_DEPOSIT_SEED, mint.as_ref(), owner.as_ref()],
+            bump,
+        )?;
+
+        Ok(true)
+    }
+}
+
+pub fn set_exchange_policy(ctx: Context<UpdateConfig>, policy: ExchangePolicy, flat_fee: u64) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_FEES)?;
+    config.exchange_policy = policy as u8;
+    config.exchange_flat_fee = flat_fee;
+
+    Ok(())
+}
+
+// Registers `owners` in one go. Remaining accounts are their deposit PDAs in the same order.
+pub fn register_exchange_deposits<'info>(
+    ctx: Context<'_, '_, '_, 'info, RegisterExchangeDeposits<'info>>,
+    owners: Vec<Pubkey>,
+) -> Result<()> {
+    RoyaltyConfig::load_current(&ctx.accounts.config)?.require_unlocked(LOCK_FEES)?;
+    require!(
+        !owners.is_empty()
+            && owners.len() <= MAX_EXCHANGE_BATCH
+            && owners.len() == ctx.remaining_accounts.len(),
+        TransferHookError::InvalidExchangeDeposit
+    );
+
+    let mint = ctx.accounts.mint.key();
+    let now = Clock::get()?.unix_timestamp;
+    let lamports = Rent::get()?.minimum_balance(ExchangeDeposit::LEN);
+    for (owner, deposit_info) in owners.iter().zip(ctx.remaining_accounts) {
+        let (address, bump) = Pubkey::find_program_address(
+            &[EXCHANGE_DEPOSIT_SEED, mint.as_ref(), owner.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            address,
+            *deposit_info.key,
+            TransferHookError::InvalidExchangeDeposit
+        );
+
+        // Fails if the owner is already registered
+        create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: deposit_info.clone(),
+                },
+                &[&[EXCHANGE_DEPOSIT_SEED, mint.as_ref(), owner.as_ref(), &[bump]]],
+            ),
+            lamports,
+            ExchangeDeposit::LEN as u64,
+            &crate::ID,
+        )?;
+
+        let data = &mut deposit_info.try_borrow_mut_data()?;
+        data[..8].copy_from_slice(&ExchangeDeposit::DISCRIMINATOR);
+        let deposit: &mut ExchangeDeposit = bytemuck::from_bytes_mut(&mut data[8..]);
+        deposit.owner = *owner;
+        deposit.mint = mint;
+        deposit.registered_at = now;
+        deposit.bump = bump;
+    }
+
+    msg!("Registered {} exchange deposit owners", owners.len());
+
+    Ok(())
+}
+
+pub fn unregister_exchange_deposit(ctx: Context<UnregisterExchangeDeposit>) -> Result<()> {
+    RoyaltyConfig::load_current(&ctx.accounts.config)?.require_unlocked(LOCK_FEES)?;
+
+    msg!(
+        "Unregistered exchange deposit owner {}",
+        ctx.accounts.exchange_deposit.load()?.owner
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterExchangeDeposits<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnregisterExchangeDeposit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == exchange_deposit.load()?.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [EXCHANGE_DEPOSIT_SEED, exchange_deposit.load()?.mint.as_ref(), exchange_deposit.load()?.owner.as_ref()],
+        bump = exchange_deposit.load()?.bump,
+    )]
+    pub exchange_deposit: AccountLoader<'info, ExchangeDeposit>,
+}
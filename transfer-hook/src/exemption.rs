@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::StateWithExtensions, state::Account as TokenAccountState,
+};
+
+use crate::config::{RoyaltyConfig, UpdateConfigWithMetaList, LOCK_FEES};
+use crate::error::TransferHookError;
+use crate::meta_list;
+
+// True when the pass ATA resolved for the source owner holds at least one pass. The ATA is
+// derived by token2022 from the meta list whether or not it exists, so a missing or foreign
+// account just means no pass.
+pub fn holds_exemption_pass(pass_account: &AccountInfo, config: &RoyaltyConfig, owner: &Pubkey) -> Result<bool> {
+    if config.exemption_pass_mint == Pubkey::default()
+        || pass_account.owner != &config.exemption_pass_token_program
+        || pass_account.data_len() == 0
+    {
+        return Ok(false);
+    }
+
+    let data = pass_account.try_borrow_data()?;
+    let Ok(account) = StateWithExtensions::<TokenAccountState>::unpack(&data) else {
+        return Ok(false);
+    };
+    Ok(account.base.mint == config.exemption_pass_mint
+        && account.base.owner == *owner
+        && account.base.amount >= 1)
+}
+
+// Pass `Pubkey::default()` as the mint to stop honouring passes
+pub fn set_exemption_pass(
+    ctx: Context<UpdateConfigWithMetaList>,
+    pass_mint: Pubkey,
+    pass_token_program: Pubkey,
+) -> Result<()> {
+    require!(
+        pass_mint == Pubkey::default()
+            || pass_token_program == anchor_spl::token::ID
+            || pass_token_program == anchor_spl::token_2022::ID,
+        TransferHookError::InvalidExemptionPass
+    );
+
+    {
+        let config = &mut ctx.accounts.config.load_mut()?;
+        config.require_unlocked(LOCK_FEES)?;
+        config.exemption_pass_mint = pass_mint;
+        config.exemption_pass_token_program = pass_token_program;
+    }
+
+    // The pass ATA is derived from the mint and token program stored in the meta list
+    meta_list::rewrite_from_config(
+        &ctx.accounts.config,
+        &ctx.accounts.extra_account_meta_list.to_account_info(),
+        ctx.accounts.mint.to_account_info().owner,
+    )
+}
@@ -0,0 +1,204 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::{self,
+    extension::{
+        confidential_transfer::ConfidentialTransferAccount, immutable_owner::ImmutableOwner,
+        interest_bearing_mint::InterestBearingConfig, permanent_delegate::PermanentDelegate,
+        transfer_fee::TransferFeeConfig, transfer_hook::TransferHookAccount, BaseStateWithExtensions,
+        StateWithExtensions,
+    },
+    state::{Account as TokenAccountState, Mint as MintState},
+};
+
+// Mints whose accounts hold wrapped lamports, topped up by system transfers and `sync_native`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MintClass {
+    Standard,
+    NativeWrapped,
+}
+
+pub fn mint_class(mint: &Pubkey, source: &TokenAccountState, destination: &TokenAccountState) -> MintClass {
+    if *mint == spl_token::native_mint::ID
+        || *mint == spl_token_2022::native_mint::ID
+        || source.is_native()
+        || destination.is_native()
+    {
+        MintClass::NativeWrapped
+    } else {
+        MintClass::Standard
+    }
+}
+
+// Wrapping funds a holder's own account and unwrapping moves balance into a temporary account of
+// the same holder before closing it, neither hands value to someone else
+pub fn is_native_wrap_move(class: MintClass, source: &TokenAccountState, destination: &TokenAccountState) -> bool {
+    class == MintClass::NativeWrapped && source.owner == destination.owner
+}
+
+// Same year length token2022 uses for interest accrual
+const SECONDS_PER_YEAR: f64 = 60.0 * 60.0 * 24.0 * 365.24;
+
+// Raw-to-UI multiplier of an interest-bearing mint at `unix_timestamp`, 1.0 without the extension.
+// Mirrors token2022's continuous compounding: the average rate up to the last rate update, then
+// the current rate since.
+pub fn interest_multiplier(mint: &AccountInfo, unix_timestamp: i64) -> Result<f64> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+    let Ok(interest_config) = mint.get_extension::<InterestBearingConfig>() else {
+        return Ok(1.0);
+    };
+
+    let initialization_timestamp = i64::from(interest_config.initialization_timestamp);
+    let last_update_timestamp = i64::from(interest_config.last_update_timestamp);
+    let pre_update_average_rate = i16::from(interest_config.pre_update_average_rate) as f64;
+    let current_rate = i16::from(interest_config.current_rate) as f64;
+
+    let pre_update_years =
+        last_update_timestamp.saturating_sub(initialization_timestamp) as f64 / SECONDS_PER_YEAR;
+    let post_update_years =
+        unix_timestamp.saturating_sub(last_update_timestamp) as f64 / SECONDS_PER_YEAR;
+
+    Ok((pre_update_average_rate / 10_000.0 * pre_update_years).exp()
+        * (current_rate / 10_000.0 * post_update_years).exp())
+}
+
+// Raw amount scaled to UI units, saturating at the u64 range
+pub fn scaled_amount(amount: u64, multiplier: f64) -> u64 {
+    (amount as f64 * multiplier) as u64
+}
+
+// True when the transfer authority is the mint's permanent delegate
+pub fn is_permanent_delegate(mint: &AccountInfo, authority: &Pubkey) -> Result<bool> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    Ok(match mint.get_extension::<PermanentDelegate>() {
+        Ok(extension) => Option::<Pubkey>::from(extension.delegate) == Some(*authority),
+        Err(_) => false,
+    })
+}
+
+// Fee the native transfer-fee extension withholds on this transfer, zero when the mint has none
+pub fn transfer_fee_extension_fee(mint: &AccountInfo, amount: u64, epoch: u64) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    Ok(match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => transfer_fee_config
+            .calculate_epoch_fee(epoch, amount)
+            .unwrap_or(0),
+        Err(_) => 0,
+    })
+}
+
+// Token2022 sets the flag on the source account only for the duration of its Execute CPI, so a
+// set flag proves the hook is running inside a real transfer rather than being called directly
+pub fn is_transferring(token_account: &AccountInfo) -> Result<bool> {
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+
+    Ok(match account.get_extension::<TransferHookAccount>() {
+        Ok(extension) => bool::from(extension.transferring),
+        Err(_) => false,
+    })
+}
+
+pub fn has_confidential_transfer_account(token_account: &AccountInfo) -> Result<bool> {
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+
+    Ok(account.get_extension::<ConfidentialTransferAccount>().is_ok())
+}
+
+// Token2022 ATAs always carry the extension, other accounts only when created with it
+pub fn has_immutable_owner(token_account: &AccountInfo) -> Result<bool> {
+    let data = token_account.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+
+    Ok(account.get_extension::<ImmutableOwner>().is_ok())
+}
+
+// A confidential transfer reaches the hook with a zero amount, the real amount is encrypted.
+// A zero amount between accounts configured for confidential transfers is treated as one.
+pub fn is_confidential_transfer(
+    amount: u64,
+    source: &AccountInfo,
+    destination: &AccountInfo,
+) -> Result<bool> {
+    if amount != 0 {
+        return Ok(false);
+    }
+    Ok(has_confidential_transfer_account(source)? || has_confidential_transfer_account(destination)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::program_option::COption;
+    use spl_token_2022::{native_mint, state::AccountState};
+
+    use super::*;
+
+    const RENT_EXEMPT_RESERVE: u64 = 2_039_280;
+
+    fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64, native: bool) -> TokenAccountState {
+        TokenAccountState {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            is_native: if native {
+                COption::Some(RENT_EXEMPT_RESERVE)
+            } else {
+                COption::None
+            },
+            state: AccountState::Initialized,
+            ..TokenAccountState::default()
+        }
+    }
+
+    #[test]
+    fn wrap_into_own_account_is_exempt() {
+        // System transfer and `sync_native` fund a temporary account, which then moves into the ATA
+        let holder = Pubkey::new_unique();
+        let temporary = token_account(&native_mint::ID, &holder, 1_000_000, true);
+        let ata = token_account(&native_mint::ID, &holder, 0, true);
+
+        let class = mint_class(&native_mint::ID, &temporary, &ata);
+        assert!(class == MintClass::NativeWrapped);
+        assert!(is_native_wrap_move(class, &temporary, &ata));
+    }
+
+    #[test]
+    fn partial_unwrap_through_temporary_account_is_exempt() {
+        // Balance moves into a temporary account of the same holder, which is then closed for lamports
+        let holder = Pubkey::new_unique();
+        let ata = token_account(&native_mint::ID, &holder, 5_000_000, true);
+        let temporary = token_account(&native_mint::ID, &holder, 0, true);
+
+        let class = mint_class(&native_mint::ID, &ata, &temporary);
+        assert!(is_native_wrap_move(class, &ata, &temporary));
+    }
+
+    #[test]
+    fn synced_balance_sent_to_someone_else_is_charged() {
+        let holder = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let synced = token_account(&native_mint::ID, &holder, 5_000_000, true);
+        let destination = token_account(&native_mint::ID, &recipient, 0, true);
+
+        let class = mint_class(&native_mint::ID, &synced, &destination);
+        assert!(class == MintClass::NativeWrapped);
+        assert!(!is_native_wrap_move(class, &synced, &destination));
+    }
+
+    #[test]
+    fn standard_mint_moves_between_own_accounts_are_charged() {
+        let mint = Pubkey::new_unique();
+        let holder = Pubkey::new_unique();
+        let source = token_account(&mint, &holder, 5_000_000, false);
+        let destination = token_account(&mint, &holder, 0, false);
+
+        let class = mint_class(&mint, &source, &destination);
+        assert!(class == MintClass::Standard);
+        assert!(!is_native_wrap_move(class, &source, &destination));
+    }
+}
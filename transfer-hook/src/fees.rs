@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+
+// Constants for royalty percentage (e.g., 5%)
+pub const ROYALTY_PERCENTAGE: u64 = 5;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Rate charged when no fee schedule segment has started
+pub const BASE_FEE_BPS: u16 = (ROYALTY_PERCENTAGE * 100) as u16;
+
+// How fractional token amounts are resolved in fee math
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RoundingMode {
+    Floor,
+    Ceil,
+    HalfUp,
+}
+
+impl RoundingMode {
+    // Decode the byte stored in zero-copy state, unknown values fall back to floor
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => RoundingMode::Ceil,
+            2 => RoundingMode::HalfUp,
+            _ => RoundingMode::Floor,
+        }
+    }
+}
+
+// amount * numerator / denominator, rounded per the given mode
+pub fn mul_div(amount: u64, numerator: u64, denominator: u64, rounding: RoundingMode) -> u64 {
+    // Widen to avoid overflow on large transfers
+    let product = amount as u128 * numerator as u128;
+    let denominator = denominator as u128;
+    let quotient = product / denominator;
+    let remainder = product % denominator;
+
+    let round_up = match rounding {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => remainder > 0,
+        RoundingMode::HalfUp => remainder * 2 >= denominator,
+    };
+
+    (quotient + round_up as u128) as u64
+}
+
+pub fn bps_fee(amount: u64, fee_bps: u16, rounding: RoundingMode) -> u64 {
+    mul_div(amount, fee_bps as u64, BPS_DENOMINATOR, rounding)
+}
+
+// Scale the rate up in proportion to how far rolling volume exceeds the target, within the
+// admin-set bounds. At or below target the base rate applies.
+pub fn dynamic_fee_bps(
+    base_bps: u16,
+    rolling_volume: u64,
+    volume_target: u64,
+    min_bps: u16,
+    max_bps: u16,
+) -> u16 {
+    let scaled = if volume_target == 0 || rolling_volume <= volume_target {
+        base_bps as u128
+    } else {
+        base_bps as u128 * rolling_volume as u128 / volume_target as u128
+    };
+    (scaled.min(max_bps as u128) as u16).max(min_bps)
+}
+
+// Clamp a computed fee to the configured floor and cap, never exceeding the transfer itself
+pub fn clamp_fee(fee: u64, amount: u64, min_fee: Option<u64>, max_fee: Option<u64>) -> u64 {
+    let mut fee = fee;
+    if let Some(min_fee) = min_fee {
+        fee = fee.max(min_fee);
+    }
+    if let Some(max_fee) = max_fee {
+        fee = fee.min(max_fee);
+    }
+    fee.min(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [RoundingMode; 3] = [RoundingMode::Floor, RoundingMode::Ceil, RoundingMode::HalfUp];
+
+    #[test]
+    fn rounding_mode_decodes_stored_bytes() {
+        assert!(RoundingMode::from_u8(0) == RoundingMode::Floor);
+        assert!(RoundingMode::from_u8(1) == RoundingMode::Ceil);
+        assert!(RoundingMode::from_u8(2) == RoundingMode::HalfUp);
+        assert!(RoundingMode::from_u8(u8::MAX) == RoundingMode::Floor);
+    }
+
+    #[test]
+    fn mul_div_exact_results_ignore_rounding() {
+        for rounding in MODES {
+            assert_eq!(mul_div(10_000, 500, BPS_DENOMINATOR, rounding), 500);
+            assert_eq!(mul_div(0, 500, BPS_DENOMINATOR, rounding), 0);
+            assert_eq!(mul_div(10_000, 0, BPS_DENOMINATOR, rounding), 0);
+        }
+    }
+
+    #[test]
+    fn mul_div_rounds_per_mode() {
+        // 3 * 1 / 4 = 0.75
+        assert_eq!(mul_div(3, 1, 4, RoundingMode::Floor), 0);
+        assert_eq!(mul_div(3, 1, 4, RoundingMode::Ceil), 1);
+        assert_eq!(mul_div(3, 1, 4, RoundingMode::HalfUp), 1);
+        // 1 * 1 / 4 = 0.25
+        assert_eq!(mul_div(1, 1, 4, RoundingMode::Floor), 0);
+        assert_eq!(mul_div(1, 1, 4, RoundingMode::Ceil), 1);
+        assert_eq!(mul_div(1, 1, 4, RoundingMode::HalfUp), 0);
+        // 1 * 1 / 2 = 0.5, half rounds up
+        assert_eq!(mul_div(1, 1, 2, RoundingMode::Floor), 0);
+        assert_eq!(mul_div(1, 1, 2, RoundingMode::Ceil), 1);
+        assert_eq!(mul_div(1, 1, 2, RoundingMode::HalfUp), 1);
+    }
+
+    #[test]
+    fn mul_div_handles_u64_max() {
+        for rounding in MODES {
+            assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX, rounding), u64::MAX);
+            assert_eq!(
+                mul_div(u64::MAX, BPS_DENOMINATOR, BPS_DENOMINATOR, rounding),
+                u64::MAX
+            );
+        }
+        // u64::MAX * 5000 / 10000 = 9223372036854775807.5
+        assert_eq!(
+            mul_div(u64::MAX, 5_000, BPS_DENOMINATOR, RoundingMode::Floor),
+            u64::MAX / 2
+        );
+        assert_eq!(
+            mul_div(u64::MAX, 5_000, BPS_DENOMINATOR, RoundingMode::Ceil),
+            u64::MAX / 2 + 1
+        );
+        assert_eq!(
+            mul_div(u64::MAX, 5_000, BPS_DENOMINATOR, RoundingMode::HalfUp),
+            u64::MAX / 2 + 1
+        );
+    }
+
+    #[test]
+    fn bps_fee_never_exceeds_the_amount() {
+        for rounding in MODES {
+            for amount in [0, 1, 3, 9_999, 10_001, u64::MAX] {
+                assert!(bps_fee(amount, BPS_DENOMINATOR as u16, rounding) == amount);
+                assert!(bps_fee(amount, 1, rounding) <= amount);
+            }
+        }
+    }
+}
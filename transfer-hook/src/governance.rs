@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+use crate::config::{UpdateConfig, LOCK_AUTHORITY};
+use crate::error::TransferHookError;
+
+// Only the canonical SPL Governance (Realms) deployment can hold the authority, a program of the
+// caller's choosing could sign for its PDAs without any vote
+pub const SPL_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+// SPL Governance (Realms) PDA seeds
+pub const ACCOUNT_GOVERNANCE_SEED: &[u8] = b"account-governance";
+pub const NATIVE_TREASURY_SEED: &[u8] = b"native-treasury";
+
+// `GovernanceAccountType` tags of a realm account, the first byte of its data
+const REALM_V1_ACCOUNT_TYPE: u8 = 1;
+const REALM_V2_ACCOUNT_TYPE: u8 = 16;
+
+pub fn governance_address(governance_program: &Pubkey, realm: &Pubkey, governed_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[ACCOUNT_GOVERNANCE_SEED, realm.as_ref(), governed_account.as_ref()],
+        governance_program,
+    )
+    .0
+}
+
+pub fn native_treasury_address(governance_program: &Pubkey, governance: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[NATIVE_TREASURY_SEED, governance.as_ref()], governance_program).0
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GovernanceAuthorityParams {
+    pub governance_program: Pubkey,
+    pub realm: Pubkey,
+    pub governed_account: Pubkey,
+    // Sign with the governance's native treasury instead of the governance account itself
+    pub use_native_treasury: bool,
+}
+
+// Hands the config authority to a Realms governance. Both the governance account and its native
+// treasury are PDAs of the governance program, which only signs for them when executing a passed
+// proposal, so every later `set_*` instruction has to go through a vote.
+pub fn set_governance_authority(
+    ctx: Context<SetGovernanceAuthority>,
+    params: GovernanceAuthorityParams,
+) -> Result<()> {
+    require_keys_eq!(
+        params.governance_program,
+        SPL_GOVERNANCE_PROGRAM_ID,
+        TransferHookError::InvalidGovernance
+    );
+
+    let realm = &ctx.accounts.realm;
+    require_keys_eq!(realm.key(), params.realm, TransferHookError::InvalidGovernance);
+    require!(
+        realm.owner == &params.governance_program
+            && matches!(
+                realm.try_borrow_data()?.first(),
+                Some(&REALM_V1_ACCOUNT_TYPE) | Some(&REALM_V2_ACCOUNT_TYPE)
+            ),
+        TransferHookError::InvalidGovernance
+    );
+
+    let governance = ctx.accounts.governance.key();
+    require_keys_eq!(
+        governance,
+        governance_address(&params.governance_program, &params.realm, &params.governed_account),
+        TransferHookError::InvalidGovernance
+    );
+    require!(
+        ctx.accounts.governance.owner == &params.governance_program,
+        TransferHookError::InvalidGovernance
+    );
+
+    let new_authority = if params.use_native_treasury {
+        native_treasury_address(&params.governance_program, &governance)
+    } else {
+        governance
+    };
+
+    let config = &mut ctx.accounts.update.config.load_mut()?;
+    config.require_unlocked(LOCK_AUTHORITY)?;
+    config.authority = new_authority;
+    // Recorded so clients can tell a governed config apart and build proposals against it
+    config.governance_program = params.governance_program;
+    config.governance = governance;
+
+    msg!("Config authority handed to governance {}", governance);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetGovernanceAuthority<'info> {
+    pub update: UpdateConfig<'info>,
+    /// CHECK: Realms realm the governance belongs to, owner and account type checked in the handler
+    pub realm: UncheckedAccount<'info>,
+    /// CHECK: Realms governance account, derivation and owner checked in the handler
+    pub governance: UncheckedAccount<'info>,
+}
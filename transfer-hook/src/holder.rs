@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+
+pub const HOLDER_STATS_SEED: &[u8] = b"holder-stats";
+
+// Per-owner activity for a mint, keyed by the owner of the source token account and written by the
+// hook when the account exists
+#[account(zero_copy)]
+pub struct HolderStats {
+    pub outbound_volume: u64,
+    pub transfer_count: u64,
+    pub last_activity_ts: i64,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl HolderStats {
+    pub const LEN: usize = 8 + std::mem::size_of::<HolderStats>();
+
+    pub fn record_outbound(&mut self, amount: u64, now: i64) {
+        self.outbound_volume = self.outbound_volume.saturating_add(amount);
+        self.transfer_count = self.transfer_count.saturating_add(1);
+        self.last_activity_ts = now;
+    }
+
+    pub fn is_stale(&self, now: i64, ttl: i64) -> bool {
+        now.saturating_sub(self.last_activity_ts) >= ttl
+    }
+}
+
+// The hook receives the holder stats PDA whether or not it has been created, so an uninitialized
+// account is skipped rather than treated as an error
+pub fn record_outbound_if_initialized(
+    holder_stats: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    if holder_stats.owner != &crate::ID || holder_stats.data_len() != HolderStats::LEN {
+        return Ok(());
+    }
+
+    let loader = AccountLoader::<HolderStats>::try_from(holder_stats)?;
+    let stats = &mut loader.load_mut()?;
+    require!(
+        stats.owner == *owner && stats.mint == *mint,
+        TransferHookError::InvalidHolderState
+    );
+    stats.record_outbound(amount, now);
+
+    Ok(())
+}
+
+// Seller's outbound volume so far for tiered fees, zero when the holder has no stats account
+pub fn outbound_volume_if_initialized(holder_stats: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> Result<u64> {
+    if holder_stats.owner != &crate::ID || holder_stats.data_len() != HolderStats::LEN {
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<HolderStats>::try_from(holder_stats)?;
+    let stats = loader.load()?;
+    require!(
+        stats.owner == *owner && stats.mint == *mint,
+        TransferHookError::InvalidHolderState
+    );
+    Ok(stats.outbound_volume)
+}
+
+pub fn initialize_holder_stats(ctx: Context<InitializeHolderStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.holder_stats.load_init()?;
+    stats.owner = ctx.accounts.owner.key();
+    stats.mint = ctx.accounts.mint.key();
+    stats.last_activity_ts = Clock::get()?.unix_timestamp;
+    stats.bump = ctx.bumps.holder_stats;
+
+    Ok(())
+}
+
+// Permissionless: anyone can close a holder PDA once the holder is empty and inactive past the TTL.
+// Rent goes to the configured destination, or to the cranker as an incentive when none is set.
+pub fn close_stale_holder_state(ctx: Context<CloseStaleHolderState>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let config = RoyaltyConfig::load_current(&ctx.accounts.config)?;
+
+    require!(
+        config.holder_state_ttl > 0,
+        TransferHookError::HolderStateGcDisabled
+    );
+    require!(
+        ctx.accounts.holder_token_account.amount == 0,
+        TransferHookError::HolderStateNotStale
+    );
+    require!(
+        ctx.accounts
+            .holder_stats
+            .load()?
+            .is_stale(now, config.holder_state_ttl),
+        TransferHookError::HolderStateNotStale
+    );
+
+    let destination = if config.gc_rent_destination == Pubkey::default() {
+        ctx.accounts.cranker.to_account_info()
+    } else {
+        require_keys_eq!(
+            ctx.accounts.rent_destination.key(),
+            config.gc_rent_destination,
+            TransferHookError::InvalidRentDestination
+        );
+        ctx.accounts.rent_destination.to_account_info()
+    };
+    drop(config);
+
+    ctx.accounts.holder_stats.close(destination)
+}
+
+#[derive(Accounts)]
+pub struct InitializeHolderStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: holder the stats are tracked for, does not need to sign
+    pub owner: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        space = HolderStats::LEN,
+        seeds = [HOLDER_STATS_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub holder_stats: AccountLoader<'info, HolderStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStaleHolderState<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    /// CHECK: only used when it matches the configured rent destination
+    #[account(mut)]
+    pub rent_destination: UncheckedAccount<'info>,
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.mint == mint.key(),
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: holder owning the stats account
+    #[account(
+        address = holder_stats.load()?.owner,
+    )]
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [HOLDER_STATS_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump = holder_stats.load()?.bump,
+    )]
+    pub holder_stats: AccountLoader<'info, HolderStats>,
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
@@ -0,0 +1,332 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use bytemuck::Zeroable;
+
+use crate::accounting::{self, VaultFlow, ROYALTY_ACCOUNTING_SEED};
+use crate::config::{RoyaltyConfig, LOCK_RECIPIENTS};
+use crate::error::TransferHookError;
+use crate::fees::{self, RoundingMode, BPS_DENOMINATOR};
+use crate::outflow::Outflow;
+use crate::rewards::{self, REWARDS_POOL_SEED};
+use crate::vault::VAULT_AUTHORITY_SEED;
+
+pub const CREATOR_LEDGER_SEED: &[u8] = b"creator-ledger";
+
+pub const MAX_CREATORS: usize = 8;
+
+#[zero_copy]
+pub struct CreatorShare {
+    pub creator: Pubkey,
+    pub accrued: u64,
+    pub claimed: u64,
+    pub weight_bps: u16,
+    pub _padding: [u8; 6],
+}
+
+impl CreatorShare {
+    pub fn unclaimed(&self) -> u64 {
+        self.accrued.saturating_sub(self.claimed)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CreatorShareEntry {
+    pub creator: Pubkey,
+    pub weight_bps: u16,
+}
+
+// Per-creator royalty balances, credited by the hook as royalties land in the vault and paid out
+// when each creator claims. Tokens stay in the vault until claimed.
+#[account(zero_copy)]
+pub struct CreatorLedger {
+    pub total_accrued: u64,
+    pub total_claimed: u64,
+    pub mint: Pubkey,
+    pub shares: [CreatorShare; MAX_CREATORS],
+    pub creator_count: u8,
+    pub bump: u8,
+    pub _padding: [u8; 6],
+}
+
+impl CreatorLedger {
+    pub const LEN: usize = 8 + std::mem::size_of::<CreatorLedger>();
+
+    pub fn shares(&self) -> &[CreatorShare] {
+        &self.shares[..self.creator_count as usize]
+    }
+
+    pub fn total_weight_bps(&self) -> u64 {
+        self.shares().iter().map(|share| share.weight_bps as u64).sum()
+    }
+
+    // Vault tokens owed to creators
+    pub fn outstanding(&self) -> u64 {
+        self.total_accrued.saturating_sub(self.total_claimed)
+    }
+
+    // Rounding up can push the creators' shares past what is left of the royalty, later creators
+    // get whatever remains of `available`
+    pub fn accrue(&mut self, royalty: u64, available: u64, rounding: RoundingMode) {
+        let count = self.creator_count as usize;
+        let mut remaining = available;
+        for share in self.shares[..count].iter_mut() {
+            let amount = fees::bps_fee(royalty, share.weight_bps, rounding).min(remaining);
+            remaining -= amount;
+            share.accrued = share.accrued.saturating_add(amount);
+            self.total_accrued = self.total_accrued.saturating_add(amount);
+        }
+    }
+
+    // Creators kept in the new list keep their balances, dropping one with an unclaimed balance fails
+    pub fn set_shares(&mut self, entries: &[CreatorShareEntry]) -> Result<()> {
+        require!(
+            entries.len() <= MAX_CREATORS
+                && entries
+                    .iter()
+                    .map(|entry| entry.weight_bps as u64)
+                    .sum::<u64>()
+                    <= BPS_DENOMINATOR
+                && entries
+                    .iter()
+                    .enumerate()
+                    .all(|(i, entry)| entries[..i].iter().all(|other| other.creator != entry.creator)),
+            TransferHookError::InvalidCreatorLedger
+        );
+        require!(
+            self.shares().iter().all(|share| {
+                share.unclaimed() == 0 || entries.iter().any(|entry| entry.creator == share.creator)
+            }),
+            TransferHookError::CreatorHasUnclaimed
+        );
+
+        let mut shares = [CreatorShare::zeroed(); MAX_CREATORS];
+        for (share, entry) in shares.iter_mut().zip(entries) {
+            if let Some(existing) = self.shares().iter().find(|share| share.creator == entry.creator) {
+                *share = *existing;
+            }
+            share.creator = entry.creator;
+            share.weight_bps = entry.weight_bps;
+        }
+        self.shares = shares;
+        self.creator_count = entries.len() as u8;
+        Ok(())
+    }
+}
+
+// Creator weights and the holder rewards share are both carved out of the same vault royalties,
+// together they can't promise more than all of it
+pub fn require_royalty_split(creator_weight_bps: u64, rewards_share_bps: u16) -> Result<()> {
+    require!(
+        creator_weight_bps.saturating_add(rewards_share_bps as u64) <= BPS_DENOMINATOR,
+        TransferHookError::RoyaltySplitExceedsTotal
+    );
+    Ok(())
+}
+
+// Sum of creator weights, zero when the mint has no ledger
+pub fn total_weight_if_initialized(creator_ledger: &AccountInfo) -> Result<u64> {
+    if creator_ledger.owner != &crate::ID || creator_ledger.data_len() != CreatorLedger::LEN {
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<CreatorLedger>::try_from(creator_ledger)?;
+    let total_weight_bps = loader.load()?.total_weight_bps();
+    Ok(total_weight_bps)
+}
+
+// The hook receives the ledger PDA on every transfer whether or not it exists
+pub fn accrue_if_initialized(
+    creator_ledger: &AccountInfo,
+    mint: &Pubkey,
+    royalty: u64,
+    available: u64,
+    rounding: RoundingMode,
+) -> Result<()> {
+    if royalty == 0 || creator_ledger.owner != &crate::ID || creator_ledger.data_len() != CreatorLedger::LEN {
+        return Ok(());
+    }
+
+    let loader = AccountLoader::<CreatorLedger>::try_from(creator_ledger)?;
+    let ledger = &mut loader.load_mut()?;
+    require_keys_eq!(ledger.mint, *mint, TransferHookError::InvalidCreatorLedger);
+    ledger.accrue(royalty, available, rounding);
+
+    Ok(())
+}
+
+// Vault tokens owed to creators, zero when the mint has no ledger
+pub fn outstanding_if_initialized(creator_ledger: &AccountInfo) -> Result<u64> {
+    if creator_ledger.owner != &crate::ID || creator_ledger.data_len() != CreatorLedger::LEN {
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<CreatorLedger>::try_from(creator_ledger)?;
+    let outstanding = loader.load()?.outstanding();
+    Ok(outstanding)
+}
+
+pub fn initialize_creator_ledger(
+    ctx: Context<InitializeCreatorLedger>,
+    entries: Vec<CreatorShareEntry>,
+) -> Result<()> {
+    let ledger = &mut ctx.accounts.creator_ledger.load_init()?;
+    ledger.mint = ctx.accounts.mint.key();
+    ledger.bump = ctx.bumps.creator_ledger;
+    ledger.set_shares(&entries)?;
+    require_royalty_split(
+        ledger.total_weight_bps(),
+        rewards::share_bps_if_initialized(&ctx.accounts.rewards_pool.to_account_info())?,
+    )
+}
+
+pub fn set_creator_shares(ctx: Context<UpdateCreatorLedger>, entries: Vec<CreatorShareEntry>) -> Result<()> {
+    RoyaltyConfig::load_current(&ctx.accounts.config)?.require_unlocked(LOCK_RECIPIENTS)?;
+
+    let ledger = &mut ctx.accounts.creator_ledger.load_mut()?;
+    ledger.set_shares(&entries)?;
+    require_royalty_split(
+        ledger.total_weight_bps(),
+        rewards::share_bps_if_initialized(&ctx.accounts.rewards_pool.to_account_info())?,
+    )
+}
+
+// Approves the signing creator for everything accrued to them so far, the creator's transfer out
+// of the vault has to follow in the same transaction.
+pub fn claim_creator_fees<'info>(ctx: Context<'_, '_, '_, 'info, ClaimCreatorFees<'info>>) -> Result<()> {
+    let creator = ctx.accounts.creator.key();
+    let amount = {
+        let ledger = &mut ctx.accounts.creator_ledger.load_mut()?;
+        let count = ledger.creator_count as usize;
+        let share = ledger.shares[..count]
+            .iter_mut()
+            .find(|share| share.creator == creator)
+            .ok_or(TransferHookError::Unauthorized)?;
+        let amount = share.unclaimed();
+        share.claimed = share.accrued;
+        ledger.total_claimed = ledger.total_claimed.saturating_add(amount);
+        amount
+    };
+    require!(
+        amount <= ctx.accounts.royalty_vault.amount,
+        TransferHookError::InsufficientVaultBalance
+    );
+
+    if amount > 0 {
+        let mint_key = ctx.accounts.mint.key();
+        let bump = ctx.accounts.config.load()?.vault_authority_bump;
+        Outflow {
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            source: &ctx.accounts.royalty_vault.to_account_info(),
+            mint: &ctx.accounts.mint.to_account_info(),
+            decimals: ctx.accounts.mint.decimals,
+            owner: &ctx.accounts.vault_authority.to_account_info(),
+            delegate: &ctx.accounts.creator.to_account_info(),
+            instructions: &ctx.accounts.instructions.to_account_info(),
+        }
+        .approve(
+            &[(ctx.accounts.destination.key(), amount)],
+            &[&[VAULT_AUTHORITY_SEED, mint_key.as_ref(), &[bump]]],
+        )?;
+        accounting::record_vault_flow_if_initialized(
+            &ctx.accounts.royalty_accounting.to_account_info(),
+            &mint_key,
+            VaultFlow::Withdrawal,
+            amount,
+        )?;
+    }
+
+    msg!("Creator {} claimed {} royalty tokens", creator, amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeCreatorLedger<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = CreatorLedger::LEN,
+        seeds = [CREATOR_LEDGER_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub creator_ledger: AccountLoader<'info, CreatorLedger>,
+    /// CHECK: rewards pool PDA, may be uninitialized, its share is checked against the weights
+    #[account(
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub rewards_pool: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCreatorLedger<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [CREATOR_LEDGER_SEED, config.load()?.mint.as_ref()],
+        bump = creator_ledger.load()?.bump,
+    )]
+    pub creator_ledger: AccountLoader<'info, CreatorLedger>,
+    /// CHECK: rewards pool PDA, may be uninitialized, its share is checked against the weights
+    #[account(
+        seeds = [REWARDS_POOL_SEED, config.load()?.mint.as_ref()],
+        bump,
+    )]
+    pub rewards_pool: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCreatorFees<'info> {
+    pub creator: Signer<'info>,
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [CREATOR_LEDGER_SEED, mint.key().as_ref()],
+        bump = creator_ledger.load()?.bump,
+    )]
+    pub creator_ledger: AccountLoader<'info, CreatorLedger>,
+    /// CHECK: PDA owning the royalty vault, signs the approval
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = config.load()?.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        token::mint = mint,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: instructions sysvar, the creator's transfer is checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,15 +1,91 @@
-use anchor_lang::{
-    prelude::*,
-    system_program::{create_account, CreateAccount},
-};
+use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
-use spl_tlv_account_resolution::{
-    state::ExtraAccountMetaList,
-};
-use spl_transfer_hook_interface::instruction::{ExecuteInstruction, TransferHookInstruction};
+
+// msg! in the Execute path only when built with the `verbose-logs` feature
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "verbose-logs")]
+        msg!($($arg)*);
+    };
+}
+
+pub mod accounting;
+pub mod amm;
+pub mod bootstrap;
+pub mod bucket;
+pub mod config;
+pub mod credits;
+pub mod dead_man;
+pub mod destination;
+pub mod dispatch;
+pub mod distribution;
+pub mod enforcement;
+pub mod error;
+pub mod exchange;
+pub mod exemption;
+pub mod extensions;
+pub mod fees;
+pub mod governance;
+pub mod holder;
+pub mod ledger;
+pub mod lock;
+pub mod meta_list;
+pub mod outflow;
+pub mod owner_list;
+pub mod payout;
+pub mod raffle;
+pub mod receipts;
+pub mod rescue;
+pub mod rewards;
+pub mod settlement;
+pub mod snapshot;
+pub mod sponsor;
+pub mod stake;
+pub mod stats;
+pub mod transfer_log;
+pub mod validation;
+pub mod vault;
+pub mod view;
+pub mod withdrawal;
+pub mod wrapper;
+
+pub use accounting::*;
+pub use amm::*;
+pub use bootstrap::*;
+pub use bucket::*;
+pub use config::*;
+pub use credits::*;
+pub use dead_man::*;
+pub use destination::*;
+pub use distribution::*;
+pub use enforcement::*;
+pub use error::TransferHookError;
+pub use exchange::*;
+pub use fees::RoundingMode;
+pub use governance::*;
+pub use holder::*;
+pub use ledger::*;
+pub use lock::*;
+pub use meta_list::META_LIST_SEED;
+pub use owner_list::*;
+pub use payout::*;
+pub use raffle::*;
+pub use receipts::*;
+pub use rescue::*;
+pub use rewards::*;
+pub use settlement::*;
+pub use snapshot::*;
+pub use sponsor::*;
+pub use stake::StakeIntegrationParams;
+pub use stats::*;
+pub use transfer_log::*;
+pub use vault::*;
+pub use view::*;
+pub use withdrawal::*;
+pub use wrapper::*;
 
 declare_id!("8BZPRLCsb7NRKwr83CuzErr7HdcB8imhk6BJAetAJgbF");
 
@@ -17,46 +93,26 @@ declare_id!("8BZPRLCsb7NRKwr83CuzErr7HdcB8imhk6BJAetAJgbF");
 pub mod transfer_hook {
     use super::*;
 
-    // Constants for royalty percentage (e.g., 5%)
-    const ROYALTY_PERCENTAGE: u64 = 5;
-
     pub fn initialize_extra_account_meta_list(
         ctx: Context<InitializeExtraAccountMetaList>,
     ) -> Result<()> {
 
-        // The addExtraAccountsToInstruction JS helper function resolving incorrectly
-        let account_metas = vec![];
-
-        // Calculate account size
-        let account_size = ExtraAccountMetaList::size_of(account_metas.len())? as u64;
-        // Calculate minimum required lamports
-        let lamports = Rent::get()?.minimum_balance(account_size as usize);
-
-        let mint = ctx.accounts.mint.key();
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            b"extra-account-metas",
-            &mint.as_ref(),
-            &[ctx.bumps.extra_account_meta_list],
-        ]];
-
-        // Create ExtraAccountMetaList account
-        create_account(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                CreateAccount {
-                    from: ctx.accounts.payer.to_account_info(),
-                    to: ctx.accounts.extra_account_meta_list.to_account_info(),
-                },
-            )
-            .with_signer(signer_seeds),
-            lamports,
-            account_size,
-            ctx.program_id,
+        // Fees are only ever sent to the account recorded here, see `accept_recipient` and
+        // `initialize_vault` for rotation.
+        // Repeating the call with another recipient fails with `MetaListMismatch` and reverts this.
+        ctx.accounts.config.load_mut()?.royalty_recipient = ctx.accounts.royalty_token_account.key();
+
+        let account_metas = meta_list::extra_account_metas(
+            &*RoyaltyConfig::load_current(&ctx.accounts.config)?,
+            &ctx.accounts.token_program.key(),
         )?;
 
-        // Initialize ExtraAccountMetaList account with extra accounts
-        ExtraAccountMetaList::init::<ExecuteInstruction>(
-            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+        meta_list::create_extra_account_meta_list(
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.extra_account_meta_list.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.mint.key(),
+            ctx.bumps.extra_account_meta_list,
             &account_metas,
         )?;
 
@@ -64,60 +120,841 @@ pub mod transfer_hook {
     }
 
     pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
-    msg!("Performing on-chain royalties logic in transfer hook!");
+        verbose_msg!("Performing on-chain royalties logic in transfer hook!");
 
-    // Calculate the royalty amount and remaining transfer amount
-    let royalty_amount = amount * ROYALTY_PERCENTAGE / 100;
-    let transfer_amount = amount - royalty_amount;
+        // Only token2022 may drive the hook, a direct call would record unverified amounts
+        validation::require_invoked_by_cpi()?;
+        require!(
+            extensions::is_transferring(&ctx.accounts.source_token.to_account_info())?,
+            TransferHookError::NotTransferring
+        );
 
-    // Transfer royalty to the royalty recipient
-    let cpi_accounts = anchor_spl::token::Transfer {
-        from: ctx.accounts.source_token.to_account_info(),
-        to: ctx.accounts.royalty_token_account.to_account_info(),
-        authority: ctx.accounts.owner.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info(); // Reference the token program from the context
-    anchor_spl::token::transfer(
-        CpiContext::new(cpi_program.clone(), cpi_accounts), // Clone the cpi_program here
-        royalty_amount,
-    )?;
-
-    // Transfer the remaining amount to the destination token account
-    let cpi_accounts_transfer = anchor_spl::token::Transfer {
-        from: ctx.accounts.source_token.to_account_info(),
-        to: ctx.accounts.destination_token.to_account_info(),
-        authority: ctx.accounts.owner.to_account_info(),
-    };
-    anchor_spl::token::transfer(
-        CpiContext::new(cpi_program, cpi_accounts_transfer), // No need to clone here again, it's already used
-        transfer_amount,
-    )?;
+        let config = RoyaltyConfig::load_current(&ctx.accounts.config)?;
+        let clock = Clock::get()?;
+        let source_owner = ctx.accounts.source_token.owner;
+        let authority = ctx.accounts.owner.key();
+        // Reported in place of an error by rules in monitor-only mode
+        let transfer = RuleViolation {
+            rule: 0,
+            mint: ctx.accounts.mint.key(),
+            source: ctx.accounts.source_token.key(),
+            destination: ctx.accounts.destination_token.key(),
+            amount,
+        };
 
-    msg!("Royalty transfer complete: {} lamports to royalty recipient", royalty_amount);
-    msg!("Remaining transfer complete: {} lamports to destination", transfer_amount);
+        // Admin lock-list, independent of the mint's freeze authority
+        config.check_rule(
+            RULE_ACCOUNT_LOCK,
+            AccountLock::is_locked(&ctx.accounts.source_lock.to_account_info())?,
+            TransferHookError::SourceAccountLocked,
+            &transfer,
+        )?;
 
-    Ok(())
-}
+        config.check_rule(
+            RULE_DENY_LIST,
+            owner_list::is_listed_if_initialized(
+                &ctx.accounts.deny_list_page.to_account_info(),
+                &ctx.accounts.mint.key(),
+                OwnerList::Deny,
+                &source_owner,
+            )?,
+            TransferHookError::SourceOwnerDenied,
+            &transfer,
+        )?;
+
+        // Only parse mint extensions when someone other than the owner moved the tokens
+        let by_permanent_delegate = authority != source_owner
+            && extensions::is_permanent_delegate(&ctx.accounts.mint.to_account_info(), &authority)?;
+        if by_permanent_delegate {
+            verbose_msg!("Transfer initiated by permanent delegate");
+            config.check_rule(
+                RULE_DELEGATE_BLOCK,
+                config.delegate_policy() == DelegatePolicy::Block,
+                TransferHookError::DelegateTransferBlocked,
+                &transfer,
+            )?;
+        }
+
+        // The amount of a confidential transfer is encrypted, percentage fees would silently be zero
+        let confidential = extensions::is_confidential_transfer(
+            amount,
+            &ctx.accounts.source_token.to_account_info(),
+            &ctx.accounts.destination_token.to_account_info(),
+        )?;
+        if confidential {
+            match config.confidential_policy() {
+                ConfidentialPolicy::Block => {
+                    config.check_rule(
+                        RULE_CONFIDENTIAL_BLOCK,
+                        true,
+                        TransferHookError::ConfidentialTransferBlocked,
+                        &transfer,
+                    )?;
+                    // Monitor-only, let it through as with `Skip`
+                    return Ok(());
+                }
+                ConfidentialPolicy::Skip => {
+                    verbose_msg!("Confidential transfer, skipping enforcement");
+                    return Ok(());
+                }
+                ConfidentialPolicy::FlatFee => {}
+            }
+        }
 
-    // Fallback instruction handler as workaround to anchor instruction discriminator check
+        // Record volume first so the dynamic fee sees this transfer
+        let mint_stats = stats::load_if_initialized(
+            &ctx.accounts.mint_stats.to_account_info(),
+            &ctx.accounts.mint.key(),
+        )?;
+        let rolling_volume = match &mint_stats {
+            Some(mint_stats) => {
+                let mint_stats = &mut mint_stats.load_mut()?;
+                mint_stats.record_volume(amount, clock.unix_timestamp, config.dynamic_volume_window);
+                Some(mint_stats.rolling_volume)
+            }
+            None => None,
+        };
+
+        // Prepaid royalties drawn from the source owner's credits, already in the vault
+        let mut credited = 0;
+
+        // Royalty owed by the source account on top of the amount token2022 has already moved
+        let royalty_amount = if confidential {
+            // Charged against the public balance
+            let fee = credits::draw_if_initialized(
+                &ctx.accounts.fee_credit.to_account_info(),
+                &ctx.accounts.mint.key(),
+                &source_owner,
+                config.confidential_flat_fee,
+            )?;
+            credited = config.confidential_flat_fee - fee;
+            fee
+        } else {
+            // Interest-bearing mints grow their UI amount over time, keep thresholds meaningful
+            let threshold_amount = if config.ui_amount_thresholds != 0 {
+                let multiplier = extensions::interest_multiplier(
+                    &ctx.accounts.mint.to_account_info(),
+                    clock.unix_timestamp,
+                )?;
+                extensions::scaled_amount(amount, multiplier)
+            } else {
+                amount
+            };
+            let allow_listed = owner_list::is_listed_if_initialized(
+                &ctx.accounts.allow_list_page.to_account_info(),
+                &ctx.accounts.mint.key(),
+                OwnerList::Allow,
+                &source_owner,
+            )?;
+            let holds_exemption_pass = exemption::holds_exemption_pass(
+                &ctx.accounts.exemption_pass_account.to_account_info(),
+                &config,
+                &source_owner,
+            )?;
+            // Only checked when exchange deposits are treated differently
+            let to_exchange = config.exchange_policy() != ExchangePolicy::Standard
+                && ExchangeDeposit::is_registered(
+                    &ctx.accounts.exchange_deposit.to_account_info(),
+                    &ctx.accounts.mint.key(),
+                    &ctx.accounts.destination_token.owner,
+                )?;
+            let native_wrap = config.native_wrap_exempt != 0 && {
+                let class = extensions::mint_class(
+                    &ctx.accounts.mint.key(),
+                    &ctx.accounts.source_token,
+                    &ctx.accounts.destination_token,
+                );
+                extensions::is_native_wrap_move(class, &ctx.accounts.source_token, &ctx.accounts.destination_token)
+            };
+            // Pool lookups only matter when buys and sells have their own rates
+            let trade_side = if config.trade_fees_enabled != 0 {
+                amm::trade_side(
+                    &ctx.accounts.source_amm_pool.to_account_info(),
+                    &ctx.accounts.destination_amm_pool.to_account_info(),
+                )?
+            } else {
+                TradeSide::Transfer
+            };
+            // Only read when tiers are configured, holder stats are otherwise write-only here. A
+            // seller without stats is a small seller and pays the base rate.
+            let lifetime_volume = if config.volume_tier_count > 0 {
+                holder::outbound_volume_if_initialized(
+                    &ctx.accounts.source_holder_stats.to_account_info(),
+                    &source_owner,
+                    &ctx.accounts.mint.key(),
+                )?
+            } else {
+                0
+            };
+            let inputs = FeeInputs {
+                amount,
+                threshold_amount,
+                now: clock.unix_timestamp,
+                source_owner,
+                by_permanent_delegate,
+                allow_listed,
+                holds_exemption_pass,
+                to_exchange,
+                to_royalty_account: ctx.accounts.destination_token.key() == config.royalty_vault
+                    || ctx.accounts.destination_token.key() == config.royalty_recipient,
+                // The rewards pool pays claims out of its own token account
+                from_royalty_vault: ctx.accounts.source_token.key() == config.royalty_vault
+                    || ctx.accounts.source_token.owner == ctx.accounts.rewards_pool.key(),
+                to_exempt_program: config.program_destination_override(
+                    &ctx.accounts.destination_token.owner,
+                    PROGRAM_DESTINATION_FEE_EXEMPT,
+                ),
+                native_wrap,
+                trade_side,
+                rolling_volume,
+                lifetime_volume,
+            };
+            let mut assessed = config.assess(&inputs, &ctx.accounts.mint);
+            // Net against the native transfer-fee extension so holders aren't charged twice
+            if config.net_extension_fee != 0 && assessed > 0 {
+                let extension_fee = extensions::transfer_fee_extension_fee(
+                    &ctx.accounts.mint.to_account_info(),
+                    amount,
+                    clock.epoch,
+                )?;
+                assessed = assessed.saturating_sub(extension_fee);
+            }
+            let charged = credits::draw_if_initialized(
+                &ctx.accounts.fee_credit.to_account_info(),
+                &ctx.accounts.mint.key(),
+                &source_owner,
+                assessed,
+            )?;
+            credited = assessed - charged;
+            charged
+        };
+
+        // Owners can't move tokens they've committed to the staking program
+        if config.stake_check_enabled != 0 {
+            let staked = stake::staked_amount(&ctx.accounts.stake_record.to_account_info(), &config)?;
+            let remaining = ctx.accounts.source_token.amount.saturating_sub(royalty_amount);
+            config.check_rule(
+                RULE_STAKE_LOCK,
+                remaining < staked,
+                TransferHookError::StakedBalanceLocked,
+                &transfer,
+            )?;
+        }
+
+        // The hook can't move tokens through token2022 from inside its transfer, so the royalty is
+        // owed by the source account until its owner transfers it to the recipient
+        let mint_key = ctx.accounts.mint.key();
+        let pays_royalties = ctx.accounts.destination_token.key() == config.royalty_recipient;
+        if royalty_amount > 0 || pays_royalties {
+            validation::validate_royalty_account(&ctx.accounts.royalty_token_account, &mint_key)?;
+        }
+        let settled = settlement::accrue_or_settle(
+            &ctx.accounts.fee_accrual.to_account_info(),
+            &ctx.accounts.source_token.key(),
+            &config,
+            ctx.accounts.source_token.amount,
+            royalty_amount,
+            if pays_royalties { amount } else { 0 },
+        )?;
+
+        if let Some(mint_stats) = &mint_stats {
+            mint_stats.load_mut()?.record_fee(royalty_amount.saturating_add(credited));
+        }
+
+        // Rewards and creator balances are accounted against the vault, so only royalties landing
+        // there can fund them: credits deposited earlier and accrued royalties paid into it. The
+        // transfer-fee extension withholds part of a payment in the vault, out of reach.
+        let recipient_is_vault = config.royalty_recipient == config.royalty_vault;
+        let received = if recipient_is_vault && settled > 0 {
+            let withheld = extensions::transfer_fee_extension_fee(
+                &ctx.accounts.mint.to_account_info(),
+                amount,
+                clock.epoch,
+            )?;
+            settled.min(amount.saturating_sub(withheld))
+        } else {
+            0
+        };
+        let vault_royalties = received.saturating_add(credited);
+        let rewards_share = rewards::accrue_if_initialized(
+            &ctx.accounts.rewards_pool.to_account_info(),
+            &ctx.accounts.mint.key(),
+            vault_royalties,
+            config.rounding(),
+        )?;
+        ledger::accrue_if_initialized(
+            &ctx.accounts.creator_ledger.to_account_info(),
+            &ctx.accounts.mint.key(),
+            vault_royalties,
+            vault_royalties.saturating_sub(rewards_share),
+            config.rounding(),
+        )?;
+
+        accounting::record_transfer_if_initialized(
+            &ctx.accounts.royalty_accounting.to_account_info(),
+            &ctx.accounts.mint.key(),
+            royalty_amount,
+            credited,
+            settled,
+            received,
+            // The destination is the vault when royalties were paid into it
+            (received > 0).then_some(ctx.accounts.destination_token.amount),
+        )?;
+
+        bucket::record_if_initialized(
+            &ctx.accounts.amount_bucket.to_account_info(),
+            &ctx.accounts.mint.key(),
+            amount,
+            royalty_amount,
+        )?;
+
+        holder::record_outbound_if_initialized(
+            &ctx.accounts.source_holder_stats.to_account_info(),
+            &source_owner,
+            &ctx.accounts.mint.key(),
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        // Token2022 has already moved `amount`, snapshots keep the balances from before it
+        snapshot::record_transfer_if_initialized(
+            &ctx.accounts.source_snapshot.to_account_info(),
+            &ctx.accounts.source_token,
+            &ctx.accounts.destination_snapshot.to_account_info(),
+            &ctx.accounts.destination_token,
+            config.current_snapshot_id,
+            amount,
+        )?;
+
+        // Qualifying transfers earn the receiving owner a ticket
+        if !config.program_destination_override(&ctx.accounts.destination_token.owner, PROGRAM_DESTINATION_NO_RAFFLE) {
+            raffle::enter_if_initialized(
+                &ctx.accounts.raffle.to_account_info(),
+                &ctx.accounts.mint.key(),
+                ctx.accounts.destination_token.owner,
+                amount,
+                clock.epoch,
+            )?;
+        }
+
+        receipts::record_if_initialized(
+            &ctx.accounts.receipt_book.to_account_info(),
+            &ctx.accounts.receipt_page.to_account_info(),
+            &ctx.accounts.mint.key(),
+            RoyaltyReceipt {
+                sequence: 0,
+                amount,
+                fee: royalty_amount,
+                slot: clock.slot,
+                payer: source_owner,
+            },
+            clock.unix_timestamp,
+        )?;
+
+        transfer_log::append_if_initialized(
+            &ctx.accounts.transfer_log.to_account_info(),
+            &ctx.accounts.mint.key(),
+            TransferLogEntry {
+                slot: clock.slot,
+                amount,
+                fee: royalty_amount,
+                source_owner,
+                destination_owner: ctx.accounts.destination_token.owner,
+            },
+        )?;
+
+        verbose_msg!("Royalty accrued: {}, paid to royalty recipient: {}", royalty_amount, settled);
+
+        Ok(())
+    }
+
+    pub fn create_hooked_mint(ctx: Context<CreateHookedMint>, params: CreateHookedMintParams) -> Result<()> {
+        bootstrap::create_hooked_mint(ctx, params)
+    }
+
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        params: InitializeConfigParams,
+    ) -> Result<()> {
+        config::initialize_config(ctx, params)
+    }
+
+    pub fn set_distributors(ctx: Context<UpdateConfig>, distributors: Vec<Pubkey>) -> Result<()> {
+        config::set_distributors(ctx, distributors)
+    }
+
+    pub fn set_min_fee_amount(ctx: Context<UpdateConfig>, min_fee_amount: u64) -> Result<()> {
+        config::set_min_fee_amount(ctx, min_fee_amount)
+    }
+
+    pub fn set_fee_bounds(
+        ctx: Context<UpdateConfig>,
+        min_fee: Option<u64>,
+        max_fee: Option<u64>,
+    ) -> Result<()> {
+        config::set_fee_bounds(ctx, min_fee, max_fee)
+    }
+
+    pub fn set_rounding_mode(ctx: Context<UpdateConfig>, rounding: RoundingMode) -> Result<()> {
+        config::set_rounding_mode(ctx, rounding)
+    }
+
+    pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
+        vault::initialize_vault(ctx)
+    }
+
+    pub fn propose_recipient(ctx: Context<UpdateConfig>, new_recipient: Pubkey) -> Result<()> {
+        config::propose_recipient(ctx, new_recipient)
+    }
+
+    pub fn accept_recipient(ctx: Context<AcceptRecipient>) -> Result<()> {
+        config::accept_recipient(ctx)
+    }
+
+    pub fn set_rotation_cooldown(ctx: Context<UpdateConfig>, cooldown_seconds: i64) -> Result<()> {
+        config::set_rotation_cooldown(ctx, cooldown_seconds)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        config::migrate_config(ctx)
+    }
+
+    pub fn migrate_extra_account_meta_list(ctx: Context<MigrateExtraAccountMetaList>) -> Result<()> {
+        config::migrate_extra_account_meta_list(ctx)
+    }
+
+    pub fn set_holder_state_gc(
+        ctx: Context<UpdateConfig>,
+        holder_state_ttl: i64,
+        gc_rent_destination: Pubkey,
+    ) -> Result<()> {
+        config::set_holder_state_gc(ctx, holder_state_ttl, gc_rent_destination)
+    }
+
+    pub fn initialize_holder_stats(ctx: Context<InitializeHolderStats>) -> Result<()> {
+        holder::initialize_holder_stats(ctx)
+    }
+
+    pub fn close_stale_holder_state(ctx: Context<CloseStaleHolderState>) -> Result<()> {
+        holder::close_stale_holder_state(ctx)
+    }
+
+    pub fn initialize_transfer_log(ctx: Context<InitializeTransferLog>, capacity: u32) -> Result<()> {
+        transfer_log::initialize_transfer_log(ctx, capacity)
+    }
+
+    pub fn resize_transfer_log(ctx: Context<ResizeTransferLog>, capacity: u32) -> Result<()> {
+        transfer_log::resize_transfer_log(ctx, capacity)
+    }
+
+    pub fn set_delegate_policy(
+        ctx: Context<UpdateConfig>,
+        policy: DelegatePolicy,
+        surcharge_bps: u16,
+    ) -> Result<()> {
+        config::set_delegate_policy(ctx, policy, surcharge_bps)
+    }
+
+    pub fn set_confidential_policy(
+        ctx: Context<UpdateConfig>,
+        policy: ConfidentialPolicy,
+        flat_fee: u64,
+    ) -> Result<()> {
+        config::set_confidential_policy(ctx, policy, flat_fee)
+    }
+
+    pub fn set_net_extension_fee(ctx: Context<UpdateConfig>, net_extension_fee: bool) -> Result<()> {
+        config::set_net_extension_fee(ctx, net_extension_fee)
+    }
+
+    pub fn harvest_withheld_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestWithheldFees<'info>>,
+    ) -> Result<()> {
+        vault::harvest_withheld_fees(ctx)
+    }
+
+    pub fn set_ui_amount_thresholds(ctx: Context<UpdateConfig>, ui_amount_thresholds: bool) -> Result<()> {
+        config::set_ui_amount_thresholds(ctx, ui_amount_thresholds)
+    }
+
+    pub fn lock_account(ctx: Context<LockAccount>) -> Result<()> {
+        lock::lock_account(ctx)
+    }
+
+    pub fn unlock_account(ctx: Context<UnlockAccount>) -> Result<()> {
+        lock::unlock_account(ctx)
+    }
+
+    pub fn set_fee_schedule(ctx: Context<UpdateConfig>, entries: Vec<FeeScheduleEntry>) -> Result<()> {
+        config::set_fee_schedule(ctx, entries)
+    }
+
+    pub fn set_volume_tiers(ctx: Context<UpdateConfig>, entries: Vec<VolumeTierEntry>) -> Result<()> {
+        config::set_volume_tiers(ctx, entries)
+    }
+
+    pub fn set_dynamic_fee(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+        volume_window: i64,
+        volume_target: u64,
+        min_fee_bps: u16,
+        max_fee_bps: u16,
+    ) -> Result<()> {
+        config::set_dynamic_fee(
+            ctx,
+            enabled,
+            volume_window,
+            volume_target,
+            min_fee_bps,
+            max_fee_bps,
+        )
+    }
+
+    pub fn initialize_mint_stats(ctx: Context<InitializeMintStats>) -> Result<()> {
+        stats::initialize_mint_stats(ctx)
+    }
+
+    pub fn set_distribution_program(
+        ctx: Context<UpdateConfig>,
+        distribution_program: Pubkey,
+        adapter: DistributionAdapterKind,
+    ) -> Result<()> {
+        config::set_distribution_program(ctx, distribution_program, adapter)
+    }
+
+    pub fn finalize_config(ctx: Context<UpdateConfig>, fields: u16) -> Result<()> {
+        config::finalize_config(ctx, fields)
+    }
+
+    pub fn set_exchange_policy(
+        ctx: Context<UpdateConfig>,
+        policy: ExchangePolicy,
+        flat_fee: u64,
+    ) -> Result<()> {
+        exchange::set_exchange_policy(ctx, policy, flat_fee)
+    }
+
+    pub fn register_exchange_deposits<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterExchangeDeposits<'info>>,
+        owners: Vec<Pubkey>,
+    ) -> Result<()> {
+        exchange::register_exchange_deposits(ctx, owners)
+    }
+
+    pub fn unregister_exchange_deposit(ctx: Context<UnregisterExchangeDeposit>) -> Result<()> {
+        exchange::unregister_exchange_deposit(ctx)
+    }
+
+    pub fn set_rule_enforcement(
+        ctx: Context<UpdateConfig>,
+        rules: u16,
+        enforcement: Enforcement,
+    ) -> Result<()> {
+        enforcement::set_rule_enforcement(ctx, rules, enforcement)
+    }
+
+    pub fn get_state(ctx: Context<GetState>) -> Result<HookState> {
+        view::get_state(ctx)
+    }
+
+    pub fn register_amm_pool(ctx: Context<RegisterAmmPool>) -> Result<()> {
+        amm::register_amm_pool(ctx)
+    }
+
+    pub fn set_trade_fees(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+        buy_fee_bps: u16,
+        sell_fee_bps: u16,
+    ) -> Result<()> {
+        config::set_trade_fees(ctx, enabled, buy_fee_bps, sell_fee_bps)
+    }
+
+    pub fn set_native_wrap_exempt(ctx: Context<UpdateConfig>, exempt: bool) -> Result<()> {
+        config::set_native_wrap_exempt(ctx, exempt)
+    }
+
+    pub fn set_dead_man_switch(
+        ctx: Context<UpdateConfig>,
+        epochs: u64,
+        action: DeadManAction,
+    ) -> Result<()> {
+        dead_man::set_dead_man_switch(ctx, epochs, action)
+    }
+
+    pub fn check_in(ctx: Context<UpdateConfig>) -> Result<()> {
+        dead_man::check_in(ctx)
+    }
+
+    pub fn trigger_dead_man_switch(ctx: Context<TriggerDeadManSwitch>) -> Result<()> {
+        dead_man::trigger_dead_man_switch(ctx)
+    }
+
+    pub fn initialize_owner_list_page(
+        ctx: Context<InitializeOwnerListPage>,
+        list: OwnerList,
+        page: u8,
+        capacity: u32,
+    ) -> Result<()> {
+        owner_list::initialize_owner_list_page(ctx, list, page, capacity)
+    }
+
+    pub fn resize_owner_list_page(ctx: Context<ResizeOwnerListPage>, capacity: u32) -> Result<()> {
+        owner_list::resize_owner_list_page(ctx, capacity)
+    }
+
+    pub fn update_owner_list(
+        ctx: Context<UpdateOwnerList>,
+        additions: Vec<Pubkey>,
+        removals: Vec<Pubkey>,
+    ) -> Result<()> {
+        owner_list::update_owner_list(ctx, additions, removals)
+    }
+
+    pub fn initialize_royalty_accounting(ctx: Context<InitializeRoyaltyAccounting>) -> Result<()> {
+        accounting::initialize_royalty_accounting(ctx)
+    }
+
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<Reconciliation> {
+        accounting::reconcile(ctx)
+    }
+
+    pub fn set_program_destination_policy(ctx: Context<UpdateConfig>, overrides: u8) -> Result<()> {
+        destination::set_program_destination_policy(ctx, overrides)
+    }
+
+    pub fn initialize_fee_accrual(ctx: Context<InitializeFeeAccrual>) -> Result<()> {
+        settlement::initialize_fee_accrual(ctx)
+    }
+
+    pub fn set_lazy_settlement(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        settlement::set_lazy_settlement(ctx, enabled)
+    }
+
+    pub fn initialize_fee_credit(ctx: Context<InitializeFeeCredit>) -> Result<()> {
+        credits::initialize_fee_credit(ctx)
+    }
+
+    pub fn deposit_credits<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositCredits<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        credits::deposit_credits(ctx, amount)
+    }
+
+    pub fn refund_credits<'info>(
+        ctx: Context<'_, '_, '_, 'info, RefundCredits<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        credits::refund_credits(ctx, amount)
+    }
+
+    pub fn initialize_amount_bucket(ctx: Context<InitializeAmountBucket>, bucket: u64) -> Result<()> {
+        bucket::initialize_amount_bucket(ctx, bucket)
+    }
+
+    pub fn forward_royalties<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForwardRoyalties<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        distribution::forward_royalties(ctx, amount)
+    }
+
+    pub fn set_stake_integration(
+        ctx: Context<UpdateConfigWithMetaList>,
+        params: StakeIntegrationParams,
+    ) -> Result<()> {
+        stake::set_stake_integration(ctx, params)
+    }
+
+    pub fn set_governance_authority(
+        ctx: Context<SetGovernanceAuthority>,
+        params: GovernanceAuthorityParams,
+    ) -> Result<()> {
+        governance::set_governance_authority(ctx, params)
+    }
+
+    pub fn take_snapshot(ctx: Context<TakeSnapshot>) -> Result<()> {
+        snapshot::take_snapshot(ctx)
+    }
+
+    pub fn set_snapshot_crank(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        snapshot::set_snapshot_crank(ctx, enabled)
+    }
+
+    pub fn initialize_holder_snapshot(ctx: Context<InitializeHolderSnapshot>) -> Result<()> {
+        snapshot::initialize_holder_snapshot(ctx)
+    }
+
+    pub fn get_snapshot_balance(ctx: Context<GetSnapshotBalance>, snapshot_id: u64) -> Result<u64> {
+        snapshot::get_snapshot_balance(ctx, snapshot_id)
+    }
+
+    pub fn initialize_rewards_pool(ctx: Context<InitializeRewardsPool>, share_bps: u16) -> Result<()> {
+        rewards::initialize_rewards_pool(ctx, share_bps)
+    }
+
+    pub fn set_rewards_share(ctx: Context<UpdateRewardsPool>, share_bps: u16) -> Result<()> {
+        rewards::set_rewards_share(ctx, share_bps)
+    }
+
+    pub fn fund_epoch<'info>(ctx: Context<'_, '_, '_, 'info, FundEpoch<'info>>) -> Result<()> {
+        rewards::fund_epoch(ctx)
+    }
+
+    pub fn claim_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>) -> Result<()> {
+        rewards::claim_rewards(ctx)
+    }
+
+    pub fn initialize_raffle(ctx: Context<InitializeRaffle>, threshold: u64) -> Result<()> {
+        raffle::initialize_raffle(ctx, threshold)
+    }
+
+    pub fn set_raffle_threshold(ctx: Context<UpdateRaffle>, threshold: u64) -> Result<()> {
+        raffle::set_raffle_threshold(ctx, threshold)
+    }
+
+    pub fn draw_raffle(ctx: Context<DrawRaffle>, winner_count: u8) -> Result<()> {
+        raffle::draw_raffle(ctx, winner_count)
+    }
+
+    pub fn initialize_receipt_book(ctx: Context<InitializeReceiptBook>, archive_window: i64) -> Result<()> {
+        receipts::initialize_receipt_book(ctx, archive_window)
+    }
+
+    pub fn set_receipt_archive_window(ctx: Context<UpdateReceiptBook>, archive_window: i64) -> Result<()> {
+        receipts::set_receipt_archive_window(ctx, archive_window)
+    }
+
+    pub fn open_receipt_page(ctx: Context<OpenReceiptPage>) -> Result<()> {
+        receipts::open_receipt_page(ctx)
+    }
+
+    pub fn close_receipt_page(ctx: Context<CloseReceiptPage>) -> Result<()> {
+        receipts::close_receipt_page(ctx)
+    }
+
+    pub fn set_exemption_pass(
+        ctx: Context<UpdateConfigWithMetaList>,
+        pass_mint: Pubkey,
+        pass_token_program: Pubkey,
+    ) -> Result<()> {
+        exemption::set_exemption_pass(ctx, pass_mint, pass_token_program)
+    }
+
+    pub fn initialize_withdrawal_policy(
+        ctx: Context<InitializeWithdrawalPolicy>,
+        params: WithdrawalPolicyParams,
+    ) -> Result<()> {
+        withdrawal::initialize_withdrawal_policy(ctx, params)
+    }
+
+    pub fn update_withdrawal_policy(
+        ctx: Context<UpdateWithdrawalPolicy>,
+        params: WithdrawalPolicyParams,
+    ) -> Result<()> {
+        withdrawal::update_withdrawal_policy(ctx, params)
+    }
+
+    pub fn propose_withdrawal(ctx: Context<ProposeWithdrawal>, amount: u64) -> Result<()> {
+        withdrawal::propose_withdrawal(ctx, amount)
+    }
+
+    pub fn approve_withdrawal(ctx: Context<ApproveWithdrawal>) -> Result<()> {
+        withdrawal::approve_withdrawal(ctx)
+    }
+
+    pub fn execute_withdrawal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteWithdrawal<'info>>,
+    ) -> Result<()> {
+        withdrawal::execute_withdrawal(ctx)
+    }
+
+    pub fn initialize_wrapper(ctx: Context<InitializeWrapper>) -> Result<()> {
+        wrapper::initialize_wrapper(ctx)
+    }
+
+    pub fn wrap(ctx: Context<Wrap>, amount: u64) -> Result<()> {
+        wrapper::wrap(ctx, amount)
+    }
+
+    pub fn unwrap(ctx: Context<Unwrap>, amount: u64) -> Result<()> {
+        wrapper::unwrap(ctx, amount)
+    }
+
+    pub fn initialize_rent_sponsor(ctx: Context<InitializeRentSponsor>) -> Result<()> {
+        sponsor::initialize_rent_sponsor(ctx)
+    }
+
+    pub fn fund_rent_sponsor(ctx: Context<FundRentSponsor>, lamports: u64) -> Result<()> {
+        sponsor::fund_rent_sponsor(ctx, lamports)
+    }
+
+    pub fn withdraw_rent_sponsor(ctx: Context<WithdrawRentSponsor>, lamports: u64) -> Result<()> {
+        sponsor::withdraw_rent_sponsor(ctx, lamports)
+    }
+
+    pub fn sponsor_holder_stats(ctx: Context<SponsorHolderStats>) -> Result<()> {
+        sponsor::sponsor_holder_stats(ctx)
+    }
+
+    pub fn sponsor_holder_snapshot(ctx: Context<SponsorHolderSnapshot>) -> Result<()> {
+        sponsor::sponsor_holder_snapshot(ctx)
+    }
+
+    pub fn initialize_payout_split(
+        ctx: Context<InitializePayoutSplit>,
+        params: PayoutSplitParams,
+    ) -> Result<()> {
+        payout::initialize_payout_split(ctx, params)
+    }
+
+    pub fn update_payout_split(ctx: Context<UpdatePayoutSplit>, params: PayoutSplitParams) -> Result<()> {
+        payout::update_payout_split(ctx, params)
+    }
+
+    pub fn distribute<'info>(ctx: Context<'_, '_, '_, 'info, Distribute<'info>>) -> Result<()> {
+        payout::distribute(ctx)
+    }
+
+    pub fn initialize_creator_ledger(
+        ctx: Context<InitializeCreatorLedger>,
+        entries: Vec<CreatorShareEntry>,
+    ) -> Result<()> {
+        ledger::initialize_creator_ledger(ctx, entries)
+    }
+
+    pub fn set_creator_shares(ctx: Context<UpdateCreatorLedger>, entries: Vec<CreatorShareEntry>) -> Result<()> {
+        ledger::set_creator_shares(ctx, entries)
+    }
+
+    pub fn claim_creator_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimCreatorFees<'info>>,
+    ) -> Result<()> {
+        ledger::claim_creator_fees(ctx)
+    }
+
+    pub fn propose_rescue(ctx: Context<ProposeRescue>, source: RescueSource, amount: u64) -> Result<()> {
+        rescue::propose_rescue(ctx, source, amount)
+    }
+
+    pub fn execute_rescue<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteRescue<'info>>) -> Result<()> {
+        rescue::execute_rescue(ctx)
+    }
+
+    pub fn cancel_rescue(ctx: Context<CancelRescue>) -> Result<()> {
+        rescue::cancel_rescue(ctx)
+    }
+
+    // Fallback instruction handler as workaround to anchor instruction discriminator check,
+    // token2022 CPIs the interface's Execute instruction on token transfer
     pub fn fallback<'info>(
         program_id: &Pubkey,
         accounts: &'info [AccountInfo<'info>],
         data: &[u8],
     ) -> Result<()> {
-        let instruction = TransferHookInstruction::unpack(data)?;
-
-        // Match instruction discriminator to transfer hook interface execute instruction  
-        // token2022 program CPIs this instruction on token transfer
-        match instruction {
-            TransferHookInstruction::Execute { amount } => {
-                let amount_bytes = amount.to_le_bytes();
-
-                // Invoke custom transfer hook instruction on our program
-                __private::__global::transfer_hook(program_id, accounts, &amount_bytes)
-            }
-            _ => return Err(ProgramError::InvalidInstructionData.into()),
-        }
+        dispatch::dispatch(program_id, accounts, data)
     }
 }
 
@@ -129,11 +966,21 @@ pub struct InitializeExtraAccountMetaList<'info> {
     /// CHECK: ExtraAccountMetaList Account, must use these seeds
     #[account(
         mut,
-        seeds = [b"extra-account-metas", mint.key().as_ref()], 
+        seeds = [META_LIST_SEED, mint.key().as_ref()], 
         bump
     )]
     pub extra_account_meta_list: AccountInfo<'info>,
     pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        has_one = mint,
+        constraint = RoyaltyConfig::record_admin_action(&config, &payer.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        token::mint = mint,
+    )]
+    pub royalty_token_account: InterfaceAccount<'info, TokenAccount>, // Royalty recipient token account
     pub token_program: Interface<'info, TokenInterface>, // Add token_program field here
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -143,11 +990,15 @@ pub struct InitializeExtraAccountMetaList<'info> {
 // The first 4 accounts are the accounts required for token transfer (source, mint, destination, owner)
 // Remaining accounts are the extra accounts required from the ExtraAccountMetaList account
 // These accounts are provided via CPI to this program from the token2022 program
+//
+// The handler rejects calls unless the source account's `transferring` flag is set, which only
+// token2022 does while it CPIs Execute. The source authority may be a delegate, so it isn't
+// constrained to the owner. The config is matched to the mint by its stored field instead of
+// re-deriving the PDA on every transfer.
 #[derive(Accounts)]
 pub struct TransferHook<'info> {
     #[account(
-        token::mint = mint, 
-        token::authority = owner,
+        token::mint = mint,
     )]
     pub source_token: InterfaceAccount<'info, TokenAccount>,
     pub mint: InterfaceAccount<'info, Mint>,
@@ -155,17 +1006,86 @@ pub struct TransferHook<'info> {
         token::mint = mint,
     )]
     pub destination_token: InterfaceAccount<'info, TokenAccount>,
-    #[account(
-        token::mint = mint,
-    )]
-    pub royalty_token_account: InterfaceAccount<'info, TokenAccount>, // Royalty recipient token account
     /// CHECK: source token account owner, can be SystemAccount or PDA owned by another program
     pub owner: UncheckedAccount<'info>,
-    /// CHECK: ExtraAccountMetaList Account,
-    #[account(
-        seeds = [b"extra-account-metas", mint.key().as_ref()], 
-        bump
-    )]
+    /// CHECK: ExtraAccountMetaList Account, derived and checked by token2022
     pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_token_account: InterfaceAccount<'info, TokenAccount>, // Royalty recipient token account, checked in `validation`
     pub token_program: Interface<'info, TokenInterface>, // Add token_program here
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.mint == mint.key(),
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_recipient == royalty_token_account.key() @ TransferHookError::InvalidRoyaltyRecipient,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    /// CHECK: source owner's holder stats PDA, derived by token2022 from the meta list and only
+    /// written when it is initialized and owned by this program
+    #[account(mut)]
+    pub source_holder_stats: UncheckedAccount<'info>,
+    /// CHECK: optional transfer log PDA, only appended to when initialized and owned by this program
+    #[account(mut)]
+    pub transfer_log: UncheckedAccount<'info>,
+    /// CHECK: lock PDA of the source token account, checked for existence in the handler
+    pub source_lock: UncheckedAccount<'info>,
+    /// CHECK: mint stats PDA, only written when initialized and owned by this program
+    #[account(mut)]
+    pub mint_stats: UncheckedAccount<'info>,
+    /// CHECK: external staking program from config
+    pub stake_program: UncheckedAccount<'info>,
+    /// CHECK: source owner's stake record, owner checked against config before reading
+    pub stake_record: UncheckedAccount<'info>,
+    /// CHECK: source token account's snapshot PDA, only written when initialized and owned by
+    /// this program
+    #[account(mut)]
+    pub source_snapshot: UncheckedAccount<'info>,
+    /// CHECK: destination token account's snapshot PDA, same as above
+    #[account(mut)]
+    pub destination_snapshot: UncheckedAccount<'info>,
+    /// CHECK: rewards pool PDA, only written when initialized and owned by this program
+    #[account(mut)]
+    pub rewards_pool: UncheckedAccount<'info>,
+    /// CHECK: raffle PDA, only written when initialized and owned by this program
+    #[account(mut)]
+    pub raffle: UncheckedAccount<'info>,
+    /// CHECK: receipt book PDA, only written when initialized and owned by this program
+    #[account(mut)]
+    pub receipt_book: UncheckedAccount<'info>,
+    /// CHECK: current receipt page, checked against the book before writing
+    #[account(mut)]
+    pub receipt_page: UncheckedAccount<'info>,
+    /// CHECK: associated token program, only used by token2022 to derive the pass ATA
+    pub associated_token_program: UncheckedAccount<'info>,
+    /// CHECK: pass mint's token program from config, only a seed of the pass ATA
+    pub exemption_pass_token_program: UncheckedAccount<'info>,
+    /// CHECK: pass mint from config, only a seed of the pass ATA
+    pub exemption_pass_mint: UncheckedAccount<'info>,
+    /// CHECK: source owner's exemption pass ATA, owner and mint checked before reading
+    pub exemption_pass_account: UncheckedAccount<'info>,
+    /// CHECK: creator ledger PDA, only written when initialized and owned by this program
+    #[account(mut)]
+    pub creator_ledger: UncheckedAccount<'info>,
+    /// CHECK: destination owner's exchange deposit PDA, checked for existence in the handler
+    pub exchange_deposit: UncheckedAccount<'info>,
+    /// CHECK: source token account's AMM pool PDA, checked for existence in the handler
+    pub source_amm_pool: UncheckedAccount<'info>,
+    /// CHECK: destination token account's AMM pool PDA, same as above
+    pub destination_amm_pool: UncheckedAccount<'info>,
+    /// CHECK: amount bucket PDA derived from the instruction data, re-derived in the handler before
+    /// it is written
+    #[account(mut)]
+    pub amount_bucket: UncheckedAccount<'info>,
+    /// CHECK: source owner's fee credit PDA, re-derived in the handler before it is drawn from
+    #[account(mut)]
+    pub fee_credit: UncheckedAccount<'info>,
+    /// CHECK: source owner's allow list page, re-derived in the handler before it is read
+    pub allow_list_page: UncheckedAccount<'info>,
+    /// CHECK: source owner's deny list page, same as above
+    pub deny_list_page: UncheckedAccount<'info>,
+    /// CHECK: source token account's fee accrual PDA, only written when initialized and owned by
+    /// this program
+    #[account(mut)]
+    pub fee_accrual: UncheckedAccount<'info>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(mut)]
+    pub royalty_accounting: UncheckedAccount<'info>,
 }
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::config::{RoyaltyConfig, LOCK_RESTRICTIONS};
+use crate::error::TransferHookError;
+
+pub const ACCOUNT_LOCK_SEED: &[u8] = b"account-lock";
+
+// Existence of this PDA blocks outbound transfers from `token_account`
+#[account]
+pub struct AccountLock {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub locked_at: i64,
+    pub bump: u8,
+}
+
+impl AccountLock {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+
+    // The hook receives the lock PDA for every source account, locked accounts are the ones
+    // where it exists and is ours
+    pub fn is_locked(account_lock: &AccountInfo) -> Result<bool> {
+        if account_lock.owner != &crate::ID || account_lock.data_len() < 8 {
+            return Ok(false);
+        }
+        Ok(account_lock.try_borrow_data()?[..8] == AccountLock::DISCRIMINATOR)
+    }
+}
+
+// Unlocking stays possible once restrictions are final, so finalizing them can't strand funds.
+// Renouncing the authority (LOCK_AUTHORITY or a dead-man Freeze) leaves no one to unlock, so from
+// then on the hook only reports locks instead of enforcing them, see `RoyaltyConfig::lock`.
+pub fn lock_account(ctx: Context<LockAccount>) -> Result<()> {
+    RoyaltyConfig::load_current(&ctx.accounts.config)?.require_unlocked(LOCK_RESTRICTIONS)?;
+
+    let account_lock = &mut ctx.accounts.account_lock;
+    account_lock.token_account = ctx.accounts.token_account.key();
+    account_lock.mint = ctx.accounts.mint.key();
+    account_lock.locked_at = Clock::get()?.unix_timestamp;
+    account_lock.bump = ctx.bumps.account_lock;
+
+    msg!("Locked token account {}", account_lock.token_account);
+
+    Ok(())
+}
+
+pub fn unlock_account(ctx: Context<UnlockAccount>) -> Result<()> {
+    msg!("Unlocked token account {}", ctx.accounts.account_lock.token_account);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LockAccount<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        token::mint = mint,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        space = AccountLock::LEN,
+        seeds = [ACCOUNT_LOCK_SEED, token_account.key().as_ref()],
+        bump
+    )]
+    pub account_lock: Account<'info, AccountLock>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockAccount<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == account_lock.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [ACCOUNT_LOCK_SEED, account_lock.token_account.as_ref()],
+        bump = account_lock.bump,
+    )]
+    pub account_lock: Account<'info, AccountLock>,
+}
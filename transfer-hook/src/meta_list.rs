@@ -0,0 +1,583 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
+use anchor_spl::associated_token;
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+use crate::accounting::ROYALTY_ACCOUNTING_SEED;
+use crate::amm::AMM_POOL_SEED;
+use crate::bucket::{AMOUNT_BUCKET_LOW_BYTES, AMOUNT_BUCKET_SEED, EXECUTE_AMOUNT_OFFSET};
+use crate::config::{RoyaltyConfig, CONFIG_SEED};
+use crate::credits::FEE_CREDIT_SEED;
+use crate::error::TransferHookError;
+use crate::exchange::EXCHANGE_DEPOSIT_SEED;
+use crate::holder::HOLDER_STATS_SEED;
+use crate::ledger::CREATOR_LEDGER_SEED;
+use crate::lock::ACCOUNT_LOCK_SEED;
+use crate::owner_list::{OwnerList, OWNER_LIST_PAGE_SEED};
+use crate::raffle::RAFFLE_SEED;
+use crate::receipts::{RECEIPT_BOOK_CURRENT_PAGE_OFFSET, RECEIPT_BOOK_SEED, RECEIPT_PAGE_SEED};
+use crate::rewards::REWARDS_POOL_SEED;
+use crate::settlement::FEE_ACCRUAL_SEED;
+use crate::snapshot::HOLDER_SNAPSHOT_SEED;
+use crate::stats::MINT_STATS_SEED;
+use crate::transfer_log::TRANSFER_LOG_SEED;
+
+pub const META_LIST_SEED: &[u8] = b"extra-account-metas";
+
+// Stake record seeds are [prefix, mint?, owner] under the configured staking program. The owner is
+// read from the source token account, the transfer authority may be a delegate.
+fn stake_record_seeds(config: &RoyaltyConfig) -> Vec<Seed> {
+    let mut seeds = vec![Seed::Literal {
+        bytes: config.stake_seed_prefix().to_vec(),
+    }];
+    if config.stake_seed_includes_mint != 0 {
+        seeds.push(Seed::AccountKey { index: 1 });
+    }
+    seeds.push(Seed::AccountData {
+        account_index: 0,
+        data_index: 32,
+        length: 32,
+    });
+    seeds
+}
+
+// Standard ATA seeds [owner, token_program, mint] for the pass mint, the owner read from the
+// source token account's data so delegated transfers still resolve the holder's pass. The pass
+// token program and mint are referenced by their metas, as literals they would overflow the
+// seed config.
+fn exemption_pass_seeds() -> Vec<Seed> {
+    vec![
+        Seed::AccountData {
+            account_index: 0,
+            data_index: 32,
+            length: 32,
+        },
+        Seed::AccountKey { index: 21 },
+        Seed::AccountKey { index: 22 },
+    ]
+}
+
+// Extra accounts resolved after the 5 accounts of the Execute instruction. Parts depend on
+// config, so every config change that affects them rewrites the list.
+// Owner list pages are [prefix, mint, list, first byte of the source owner]
+fn owner_list_page_seeds(list: OwnerList) -> Vec<Seed> {
+    vec![
+        Seed::Literal {
+            bytes: OWNER_LIST_PAGE_SEED.to_vec(),
+        },
+        Seed::AccountKey { index: 1 },
+        Seed::Literal {
+            bytes: vec![list as u8],
+        },
+        Seed::AccountData {
+            account_index: 0,
+            data_index: 32,
+            length: 1,
+        },
+    ]
+}
+
+pub fn extra_account_metas(
+    config: &RoyaltyConfig,
+    token_program: &Pubkey,
+) -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![
+        // index 5, royalty recipient token account, normally the vault from `initialize_vault`
+        ExtraAccountMeta::new_with_pubkey(&config.royalty_recipient, false, true)?,
+        // index 6, token program
+        ExtraAccountMeta::new_with_pubkey(token_program, false, false)?,
+        // index 7, royalty config PDA
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: CONFIG_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            false,
+        )?,
+        // index 8, source owner's holder stats PDA, may be uninitialized. The owner is read from the
+        // source token account, the transfer authority may be a delegate.
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: HOLDER_STATS_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountData {
+                    account_index: 0,
+                    data_index: 32,
+                    length: 32,
+                },
+            ],
+            false,
+            true,
+        )?,
+        // index 9, optional transfer log PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: TRANSFER_LOG_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?,
+        // index 10, lock PDA of the source token account, only exists while it is locked
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: ACCOUNT_LOCK_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            false,
+        )?,
+        // index 11, mint stats PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: MINT_STATS_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?,
+        // index 12, external staking program, the system program until configured
+        ExtraAccountMeta::new_with_pubkey(&config.stake_program, false, false)?,
+        // index 13, source owner's stake record in the staking program
+        ExtraAccountMeta::new_external_pda_with_seeds(12, &stake_record_seeds(config), false, false)?,
+        // index 14, source token account's snapshot PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: HOLDER_SNAPSHOT_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            true,
+        )?,
+        // index 15, destination token account's snapshot PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: HOLDER_SNAPSHOT_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountKey { index: 2 },
+            ],
+            false,
+            true,
+        )?,
+        // index 16, holder rewards pool PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: REWARDS_POOL_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?,
+        // index 17, raffle PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: RAFFLE_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?,
+        // index 18, receipt book PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: RECEIPT_BOOK_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?,
+        // index 19, the book's current receipt page, derived from the page number stored in it
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: RECEIPT_PAGE_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountData {
+                    account_index: 18,
+                    data_index: RECEIPT_BOOK_CURRENT_PAGE_OFFSET,
+                    length: 8,
+                },
+            ],
+            false,
+            true,
+        )?,
+        // index 20, associated token program
+        ExtraAccountMeta::new_with_pubkey(&associated_token::ID, false, false)?,
+        // index 21, token program of the exemption pass mint, the system program until configured
+        ExtraAccountMeta::new_with_pubkey(&config.exemption_pass_token_program, false, false)?,
+        // index 22, exemption pass mint
+        ExtraAccountMeta::new_with_pubkey(&config.exemption_pass_mint, false, false)?,
+        // index 23, source owner's exemption pass ATA, may not exist
+        ExtraAccountMeta::new_external_pda_with_seeds(20, &exemption_pass_seeds(), false, false)?,
+        // index 24, creator ledger PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: CREATOR_LEDGER_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?,
+        // index 25, destination owner's exchange deposit PDA, may not exist
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: EXCHANGE_DEPOSIT_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountData {
+                    account_index: 2,
+                    data_index: 32,
+                    length: 32,
+                },
+            ],
+            false,
+            false,
+        )?,
+        // index 26, source token account's AMM pool PDA, exists when the source is a pool vault
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: AMM_POOL_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            false,
+        )?,
+        // index 27, destination token account's AMM pool PDA
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: AMM_POOL_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 2 },
+            ],
+            false,
+            false,
+        )?,
+        // index 28, counter PDA of the transfer's amount bucket, seeded by the high bytes of the
+        // amount in the Execute instruction data, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: AMOUNT_BUCKET_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::InstructionData {
+                    index: (EXECUTE_AMOUNT_OFFSET + AMOUNT_BUCKET_LOW_BYTES) as u8,
+                    length: (8 - AMOUNT_BUCKET_LOW_BYTES) as u8,
+                },
+            ],
+            false,
+            true,
+        )?,
+        // index 29, source owner's fee credit PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: FEE_CREDIT_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+                Seed::AccountData {
+                    account_index: 0,
+                    data_index: 32,
+                    length: 32,
+                },
+            ],
+            false,
+            true,
+        )?,
+        // index 30, allow list page of the source owner, chosen by the first byte of the owner key
+        ExtraAccountMeta::new_with_seeds(&owner_list_page_seeds(OwnerList::Allow), false, false)?,
+        // index 31, deny list page of the source owner
+        ExtraAccountMeta::new_with_seeds(&owner_list_page_seeds(OwnerList::Deny), false, false)?,
+        // index 32, source token account's fee accrual PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: FEE_ACCRUAL_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            true,
+        )?,
+        // index 33, royalty accounting PDA, may be uninitialized
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: ROYALTY_ACCOUNTING_SEED.to_vec(),
+                },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            true,
+        )?,
+    ])
+}
+
+// What an existing meta list PDA holds compared to the list we would write
+#[derive(PartialEq, Eq)]
+pub enum MetaListState {
+    // No data yet, possibly pre-funded with lamports
+    Empty,
+    // Exactly `account_metas`, another setup flow got there first
+    Initialized,
+}
+
+pub fn meta_list_state(
+    extra_account_meta_list: &AccountInfo,
+    account_metas: &[ExtraAccountMeta],
+) -> Result<MetaListState> {
+    if extra_account_meta_list.data_is_empty() {
+        return Ok(MetaListState::Empty);
+    }
+    require_keys_eq!(
+        *extra_account_meta_list.owner,
+        crate::ID,
+        TransferHookError::MetaListForeignData
+    );
+
+    let mut expected = vec![0u8; ExtraAccountMetaList::size_of(account_metas.len())?];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut expected, account_metas)?;
+    require!(
+        *extra_account_meta_list.try_borrow_data()? == *expected,
+        TransferHookError::MetaListMismatch
+    );
+
+    Ok(MetaListState::Initialized)
+}
+
+// Create the meta list PDA of `mint` sized for `account_metas` and write them. Idempotent, a list
+// already holding exactly `account_metas` is left alone so concurrent setup flows both succeed.
+pub fn create_extra_account_meta_list<'info>(
+    payer: &AccountInfo<'info>,
+    extra_account_meta_list: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mint: &Pubkey,
+    bump: u8,
+    account_metas: &[ExtraAccountMeta],
+) -> Result<()> {
+    if meta_list_state(extra_account_meta_list, account_metas)? == MetaListState::Initialized {
+        msg!("Meta list already initialized");
+        return Ok(());
+    }
+
+    // Calculate account size
+    let account_size = ExtraAccountMetaList::size_of(account_metas.len())? as u64;
+    // Calculate minimum required lamports
+    let lamports = Rent::get()?.minimum_balance(account_size as usize);
+
+    let signer_seeds: &[&[&[u8]]] = &[&[META_LIST_SEED, mint.as_ref(), &[bump]]];
+
+    let current_lamports = extra_account_meta_list.lamports();
+    if current_lamports == 0 {
+        // Create ExtraAccountMetaList account
+        create_account(
+            CpiContext::new(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: extra_account_meta_list.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            lamports,
+            account_size,
+            &crate::ID,
+        )?;
+    } else {
+        // Anyone can send lamports to the address, which makes create_account fail. Top up to rent
+        // exemption and take the account over instead.
+        if current_lamports < lamports {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: extra_account_meta_list.clone(),
+                    },
+                ),
+                lamports - current_lamports,
+            )?;
+        }
+        allocate(
+            CpiContext::new(
+                system_program.clone(),
+                Allocate {
+                    account_to_allocate: extra_account_meta_list.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            account_size,
+        )?;
+        assign(
+            CpiContext::new(
+                system_program.clone(),
+                Assign {
+                    account_to_assign: extra_account_meta_list.clone(),
+                },
+            )
+            .with_signer(signer_seeds),
+            &crate::ID,
+        )?;
+    }
+
+    // Initialize ExtraAccountMetaList account with extra accounts
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut extra_account_meta_list.try_borrow_mut_data()?,
+        account_metas,
+    )?;
+
+    Ok(())
+}
+
+pub fn rewrite_from_config(
+    config: &AccountLoader<RoyaltyConfig>,
+    extra_account_meta_list: &AccountInfo,
+    token_program: &Pubkey,
+) -> Result<()> {
+    let account_metas = extra_account_metas(&*RoyaltyConfig::load_current(config)?, token_program)?;
+    rewrite_extra_account_metas(extra_account_meta_list, &account_metas)
+}
+
+// Grow or shrink an existing meta list to hold `len` metas, topping up rent from `payer`. Lists
+// written by older versions hold fewer metas than `extra_account_metas` now returns.
+pub fn resize_extra_account_meta_list<'info>(
+    payer: &AccountInfo<'info>,
+    extra_account_meta_list: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+) -> Result<()> {
+    let account_size = ExtraAccountMetaList::size_of(len)?;
+    if extra_account_meta_list.data_len() == account_size {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(account_size);
+    let shortfall = rent.saturating_sub(extra_account_meta_list.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: extra_account_meta_list.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    // Rewriting zero-fills the whole list, so the new bytes needn't be
+    extra_account_meta_list.realloc(account_size, false)?;
+
+    Ok(())
+}
+
+// Rewrite an existing meta list in place, the number of metas must match its allocation
+pub fn rewrite_extra_account_metas(
+    extra_account_meta_list: &AccountInfo,
+    account_metas: &[ExtraAccountMeta],
+) -> Result<()> {
+    let mut data = extra_account_meta_list.try_borrow_mut_data()?;
+    data.fill(0);
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, account_metas)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+    use crate::stake::MAX_STAKE_SEED_PREFIX_LEN;
+
+    // Every optional feature that feeds a seed or a fixed key into the list
+    fn config_with_every_feature() -> RoyaltyConfig {
+        let mut config = RoyaltyConfig::zeroed();
+        config.royalty_recipient = Pubkey::new_unique();
+        config.stake_program = Pubkey::new_unique();
+        config.stake_seed_prefix[..MAX_STAKE_SEED_PREFIX_LEN].fill(b's');
+        config.stake_seed_prefix_len = MAX_STAKE_SEED_PREFIX_LEN as u8;
+        config.stake_seed_includes_mint = 1;
+        config.exemption_pass_mint = Pubkey::new_unique();
+        config.exemption_pass_token_program = anchor_spl::token_2022::ID;
+        config
+    }
+
+    #[test]
+    fn every_feature_packs_into_the_meta_list() {
+        let config = config_with_every_feature();
+        let account_metas = extra_account_metas(&config, &anchor_spl::token_2022::ID).unwrap();
+
+        let mut data = vec![0u8; ExtraAccountMetaList::size_of(account_metas.len()).unwrap()];
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &account_metas).unwrap();
+    }
+
+    #[test]
+    fn longest_stake_prefix_fills_the_seed_config() {
+        let config = config_with_every_feature();
+        let seeds = stake_record_seeds(&config);
+
+        assert!(ExtraAccountMeta::new_external_pda_with_seeds(12, &seeds, false, false).is_ok());
+    }
+
+    #[test]
+    fn stake_prefix_past_the_maximum_does_not_pack() {
+        let mut config = config_with_every_feature();
+        config.stake_seed_prefix_len = MAX_STAKE_SEED_PREFIX_LEN as u8 + 1;
+        let seeds = stake_record_seeds(&config);
+
+        assert!(ExtraAccountMeta::new_external_pda_with_seeds(12, &seeds, false, false).is_err());
+    }
+
+    #[test]
+    fn pass_ata_seeds_reference_the_pass_metas() {
+        let config = config_with_every_feature();
+        let account_metas = extra_account_metas(&config, &anchor_spl::token_2022::ID).unwrap();
+
+        // Metas start at Execute account index 5
+        assert_eq!(
+            account_metas[21 - 5],
+            ExtraAccountMeta::new_with_pubkey(&config.exemption_pass_token_program, false, false).unwrap()
+        );
+        assert_eq!(
+            account_metas[22 - 5],
+            ExtraAccountMeta::new_with_pubkey(&config.exemption_pass_mint, false, false).unwrap()
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::Instruction,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_spl::{
+    token_2022::spl_token_2022::instruction::transfer_checked,
+    token_interface::{self, Approve},
+};
+
+use crate::error::TransferHookError;
+
+// Token2022 runs the hook inside every transfer of the mint and the runtime rejects re-entering a
+// program through CPI, so tokens can't leave program-owned accounts by this program transferring
+// them. The owning PDA approves a signer of the instruction for the total instead, and the signer
+// moves each leg with a transfer_checked right after this instruction in the same transaction.
+pub struct Outflow<'a, 'info> {
+    pub token_program: &'a AccountInfo<'info>,
+    pub source: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub decimals: u8,
+    // PDA owning `source`, signs the approval
+    pub owner: &'a AccountInfo<'info>,
+    pub delegate: &'a AccountInfo<'info>,
+    pub instructions: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> Outflow<'a, 'info> {
+    // `legs` are (destination, amount), zero legs need no transfer. Returns the total approved.
+    pub fn approve(&self, legs: &[(Pubkey, u64)], signer_seeds: &[&[&[u8]]]) -> Result<u64> {
+        let current = load_current_index_checked(self.instructions)? as usize;
+        let mut total: u64 = 0;
+        let legs = legs.iter().filter(|(_, amount)| *amount > 0);
+        for (offset, (destination, amount)) in legs.enumerate() {
+            let expected = transfer_checked(
+                self.token_program.key,
+                self.source.key,
+                self.mint.key,
+                destination,
+                self.delegate.key,
+                &[],
+                *amount,
+                self.decimals,
+            )?;
+            require_following_transfer(self.instructions, current + 1 + offset, &expected)?;
+            total = total
+                .checked_add(*amount)
+                .ok_or(TransferHookError::InsufficientVaultBalance)?;
+        }
+
+        if total > 0 {
+            token_interface::approve(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    Approve {
+                        to: self.source.clone(),
+                        delegate: self.delegate.clone(),
+                        authority: self.owner.clone(),
+                    },
+                    signer_seeds,
+                ),
+                total,
+            )?;
+        }
+
+        Ok(total)
+    }
+}
+
+// Transfers into program-owned accounts are made by their owners for the same reason, and checked
+// the same way
+pub fn require_following_transfer(
+    instructions: &AccountInfo,
+    index: usize,
+    expected: &Instruction,
+) -> Result<()> {
+    let transfer = load_instruction_at_checked(index, instructions)
+        .map_err(|_| TransferHookError::OutflowTransferMissing)?;
+    require!(
+        is_leg(&transfer, expected),
+        TransferHookError::OutflowTransferMissing
+    );
+    Ok(())
+}
+
+// The hook's extra accounts follow source, mint, destination and authority, only those are pinned
+fn is_leg(instruction: &Instruction, expected: &Instruction) -> bool {
+    instruction.program_id == expected.program_id
+        && instruction.data == expected.data
+        && instruction.accounts.len() >= expected.accounts.len()
+        && instruction
+            .accounts
+            .iter()
+            .zip(&expected.accounts)
+            .all(|(account, expected)| account.pubkey == expected.pubkey)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_spl::token_2022::spl_token_2022;
+
+    use super::*;
+
+    #[test]
+    fn matches_only_the_approved_transfer() {
+        let [source, mint, destination, owner, delegate] = [(); 5].map(|_| Pubkey::new_unique());
+        let transfer = |destination: &Pubkey, authority: &Pubkey, amount| {
+            let program = spl_token_2022::ID;
+            transfer_checked(&program, &source, &mint, destination, authority, &[], amount, 6)
+                .unwrap()
+        };
+        let expected = transfer(&destination, &delegate, 500);
+
+        let mut with_extras = expected.clone();
+        with_extras.accounts.push(AccountMeta::new_readonly(crate::ID, false));
+        assert!(is_leg(&with_extras, &expected));
+        assert!(!is_leg(&transfer(&destination, &delegate, 501), &expected));
+        // Signed by the owner rather than the approved delegate
+        assert!(!is_leg(&transfer(&destination, &owner, 500), &expected));
+        assert!(!is_leg(&transfer(&owner, &delegate, 500), &expected));
+    }
+}
@@ -0,0 +1,262 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::config::{RoyaltyConfig, LOCK_FEES, LOCK_RESTRICTIONS};
+use crate::error::TransferHookError;
+use crate::validation;
+
+pub const OWNER_LIST_PAGE_SEED: &[u8] = b"owner-list-page";
+
+// Owners a page can be created or grown by at once, within the 10KiB a single instruction can
+// allocate or grow an account by
+pub const OWNER_LIST_PAGE_GROWTH: u32 = 300;
+
+// 256 pages of this size hold over half a million owners per list
+pub const MAX_OWNER_LIST_PAGE_CAPACITY: u32 = 2048;
+
+// Upper bound on owners added or removed per instruction
+pub const MAX_OWNER_LIST_BATCH: usize = 32;
+
+// Owner lists too large for a PDA per wallet. Owners are spread over 256 pages by the first byte of
+// their key, which token2022 reads straight from the source token account when resolving the page.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OwnerList {
+    // Source owners exempt from royalties, like distributors but without the config's limit
+    Allow,
+    // Source owners whose outbound transfers are rejected
+    Deny,
+}
+
+impl OwnerList {
+    // Group that freezes changes to the list, see `finalize_config`
+    fn lock_group(self) -> u16 {
+        match self {
+            OwnerList::Allow => LOCK_FEES,
+            OwnerList::Deny => LOCK_RESTRICTIONS,
+        }
+    }
+}
+
+// Header of one page, `capacity` sorted owner keys follow it in the account data
+#[account(zero_copy)]
+pub struct OwnerListPage {
+    pub mint: Pubkey,
+    pub count: u32,
+    pub capacity: u32,
+    pub list: u8,
+    pub page: u8,
+    pub bump: u8,
+    pub _padding: [u8; 5],
+}
+
+impl OwnerListPage {
+    pub const HEADER_LEN: usize = 8 + std::mem::size_of::<OwnerListPage>();
+
+    pub fn space(capacity: u32) -> usize {
+        Self::HEADER_LEN + capacity as usize * 32
+    }
+
+    pub fn page_of(owner: &Pubkey) -> u8 {
+        owner.to_bytes()[0]
+    }
+}
+
+// Binary search over the sorted keys of a page
+pub fn contains(keys: &[Pubkey], owner: &Pubkey) -> bool {
+    keys.binary_search(owner).is_ok()
+}
+
+// Keeps `keys[..count]` sorted, returns the new count. Owners already listed are skipped.
+pub fn insert(keys: &mut [Pubkey], count: usize, owner: &Pubkey) -> Result<usize> {
+    let Err(index) = keys[..count].binary_search(owner) else {
+        return Ok(count);
+    };
+    require!(count < keys.len(), TransferHookError::OwnerListPageFull);
+    keys.copy_within(index..count, index + 1);
+    keys[index] = *owner;
+    Ok(count + 1)
+}
+
+// Returns the new count, owners not listed are skipped
+pub fn remove(keys: &mut [Pubkey], count: usize, owner: &Pubkey) -> usize {
+    let Ok(index) = keys[..count].binary_search(owner) else {
+        return count;
+    };
+    keys.copy_within(index + 1..count, index);
+    keys[count - 1] = Pubkey::default();
+    count - 1
+}
+
+fn split_page(data: &mut [u8]) -> (&mut OwnerListPage, &mut [Pubkey]) {
+    let (header, keys) = data[8..].split_at_mut(OwnerListPage::HEADER_LEN - 8);
+    let header: &mut OwnerListPage = bytemuck::from_bytes_mut(header);
+    let keys: &mut [Pubkey] = bytemuck::cast_slice_mut(&mut keys[..header.capacity as usize * 32]);
+    (header, keys)
+}
+
+// The hook receives the source owner's page of each list whether or not it has been created
+pub fn is_listed_if_initialized(
+    owner_list_page: &AccountInfo,
+    mint: &Pubkey,
+    list: OwnerList,
+    owner: &Pubkey,
+) -> Result<bool> {
+    if owner_list_page.owner != &crate::ID || owner_list_page.data_len() < OwnerListPage::HEADER_LEN {
+        return Ok(false);
+    }
+
+    let data = owner_list_page.try_borrow_data()?;
+    require!(
+        data[..8] == OwnerListPage::DISCRIMINATOR,
+        TransferHookError::InvalidOwnerListPage
+    );
+    let header: &OwnerListPage = bytemuck::from_bytes(&data[8..OwnerListPage::HEADER_LEN]);
+    let page = OwnerListPage::page_of(owner);
+    validation::require_derived(
+        owner_list_page,
+        &[OWNER_LIST_PAGE_SEED, mint.as_ref(), &[list as u8], &[page]],
+        header.bump,
+    )?;
+    let end = OwnerListPage::HEADER_LEN + header.count as usize * 32;
+    let keys: &[Pubkey] = bytemuck::cast_slice(&data[OwnerListPage::HEADER_LEN..end]);
+
+    Ok(contains(keys, owner))
+}
+
+pub fn initialize_owner_list_page(
+    ctx: Context<InitializeOwnerListPage>,
+    list: OwnerList,
+    page: u8,
+    capacity: u32,
+) -> Result<()> {
+    require!(
+        capacity > 0 && capacity <= OWNER_LIST_PAGE_GROWTH,
+        TransferHookError::InvalidOwnerListPage
+    );
+
+    let owner_list_page = &mut ctx.accounts.owner_list_page.load_init()?;
+    owner_list_page.mint = ctx.accounts.mint.key();
+    owner_list_page.capacity = capacity;
+    owner_list_page.list = list as u8;
+    owner_list_page.page = page;
+    owner_list_page.bump = ctx.bumps.owner_list_page;
+
+    Ok(())
+}
+
+// Grows a page, listed owners are kept. Large pages are reached over several calls.
+pub fn resize_owner_list_page(ctx: Context<ResizeOwnerListPage>, capacity: u32) -> Result<()> {
+    let owner_list_page = &mut ctx.accounts.owner_list_page.load_mut()?;
+    require!(
+        capacity >= owner_list_page.capacity
+            && capacity - owner_list_page.capacity <= OWNER_LIST_PAGE_GROWTH
+            && capacity <= MAX_OWNER_LIST_PAGE_CAPACITY,
+        TransferHookError::InvalidOwnerListPage
+    );
+    owner_list_page.capacity = capacity;
+
+    Ok(())
+}
+
+// Every owner must belong to the given page. Removing denied owners stays possible once
+// restrictions are final, so finalizing can't strand funds.
+pub fn update_owner_list(
+    ctx: Context<UpdateOwnerList>,
+    additions: Vec<Pubkey>,
+    removals: Vec<Pubkey>,
+) -> Result<()> {
+    let mut data = ctx.accounts.owner_list_page.as_ref().try_borrow_mut_data()?;
+    let (header, keys) = split_page(&mut data);
+    let list = if header.list == OwnerList::Deny as u8 {
+        OwnerList::Deny
+    } else {
+        OwnerList::Allow
+    };
+    require!(
+        !additions.is_empty() || !removals.is_empty(),
+        TransferHookError::InvalidOwnerListPage
+    );
+    require!(
+        additions.len() + removals.len() <= MAX_OWNER_LIST_BATCH
+            && additions
+                .iter()
+                .chain(removals.iter())
+                .all(|owner| OwnerListPage::page_of(owner) == header.page),
+        TransferHookError::InvalidOwnerListPage
+    );
+    let config = RoyaltyConfig::load_current(&ctx.accounts.config)?;
+    if !additions.is_empty() || list == OwnerList::Allow {
+        config.require_unlocked(list.lock_group())?;
+    }
+
+    let mut count = header.count as usize;
+    for owner in removals.iter() {
+        count = remove(keys, count, owner);
+    }
+    for owner in additions.iter() {
+        count = insert(keys, count, owner)?;
+    }
+    header.count = count as u32;
+
+    msg!("Owner list page {} holds {} owners", header.page, count);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(list: OwnerList, page: u8, capacity: u32)]
+pub struct InitializeOwnerListPage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = OwnerListPage::space(capacity),
+        seeds = [OWNER_LIST_PAGE_SEED, mint.key().as_ref(), &[list as u8], &[page]],
+        bump
+    )]
+    pub owner_list_page: AccountLoader<'info, OwnerListPage>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u32)]
+pub struct ResizeOwnerListPage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == owner_list_page.load()?.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        realloc = OwnerListPage::space(capacity),
+        realloc::payer = authority,
+        realloc::zero = true,
+    )]
+    pub owner_list_page: AccountLoader<'info, OwnerListPage>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOwnerList<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == owner_list_page.load()?.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(mut)]
+    pub owner_list_page: AccountLoader<'info, OwnerListPage>,
+}
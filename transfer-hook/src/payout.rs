@@ -0,0 +1,272 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::accounting::{self, VaultFlow, ROYALTY_ACCOUNTING_SEED};
+use crate::config::{RoyaltyConfig, LOCK_RECIPIENTS};
+use crate::error::TransferHookError;
+use crate::fees::{self, RoundingMode, BPS_DENOMINATOR};
+use crate::ledger::CREATOR_LEDGER_SEED;
+use crate::outflow::Outflow;
+use crate::rewards::REWARDS_POOL_SEED;
+use crate::vault::{self, VAULT_AUTHORITY_SEED};
+
+pub const PAYOUT_SPLIT_SEED: &[u8] = b"payout-split";
+
+pub const MAX_PAYOUT_RECIPIENTS: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PayoutRecipient {
+    pub token_account: Pubkey,
+    pub weight_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PayoutSplitParams {
+    // Weights must add up to 10000
+    pub recipients: Vec<PayoutRecipient>,
+    // Minimum seconds between distributions
+    pub interval: i64,
+    // Share of each distribution paid to whoever cranks it
+    pub bounty_bps: u16,
+}
+
+// How `distribute` splits the royalty vault between recipients
+#[account]
+pub struct PayoutSplit {
+    pub mint: Pubkey,
+    pub recipients: Vec<PayoutRecipient>,
+    pub interval: i64,
+    pub bounty_bps: u16,
+    pub last_distributed_ts: i64,
+    pub total_distributed: u64,
+    pub total_bounties: u64,
+    pub bump: u8,
+}
+
+impl PayoutSplit {
+    pub const LEN: usize = 8 + 32 + (4 + 34 * MAX_PAYOUT_RECIPIENTS) + 8 + 2 + 8 + 8 + 8 + 1;
+
+    fn apply(&mut self, params: PayoutSplitParams) -> Result<()> {
+        require!(
+            !params.recipients.is_empty()
+                && params.recipients.len() <= MAX_PAYOUT_RECIPIENTS
+                && params
+                    .recipients
+                    .iter()
+                    .map(|recipient| recipient.weight_bps as u64)
+                    .sum::<u64>()
+                    == BPS_DENOMINATOR,
+            TransferHookError::InvalidPayoutSplit
+        );
+        require!(params.interval >= 0, TransferHookError::InvalidCooldown);
+        require!(
+            params.bounty_bps as u64 <= BPS_DENOMINATOR,
+            TransferHookError::InvalidBps
+        );
+
+        self.recipients = params.recipients;
+        self.interval = params.interval;
+        self.bounty_bps = params.bounty_bps;
+        Ok(())
+    }
+
+    // Per-recipient amounts of `total`, the last recipient absorbs rounding so nothing is left over.
+    // Rounding up can't hand out more than `total`, earlier shares are capped at what remains.
+    pub fn shares(&self, total: u64, rounding: RoundingMode) -> Vec<u64> {
+        let mut remaining = total;
+        let mut shares: Vec<u64> = self.recipients[..self.recipients.len() - 1]
+            .iter()
+            .map(|recipient| {
+                let share = fees::mul_div(total, recipient.weight_bps as u64, BPS_DENOMINATOR, rounding)
+                    .min(remaining);
+                remaining -= share;
+                share
+            })
+            .collect();
+        shares.push(remaining);
+        shares
+    }
+}
+
+pub fn initialize_payout_split(ctx: Context<InitializePayoutSplit>, params: PayoutSplitParams) -> Result<()> {
+    let split = &mut ctx.accounts.payout_split;
+    split.mint = ctx.accounts.mint.key();
+    split.bump = ctx.bumps.payout_split;
+    split.apply(params)
+}
+
+pub fn update_payout_split(ctx: Context<UpdatePayoutSplit>, params: PayoutSplitParams) -> Result<()> {
+    RoyaltyConfig::load_current(&ctx.accounts.config)?.require_unlocked(LOCK_RECIPIENTS)?;
+    ctx.accounts.payout_split.apply(params)
+}
+
+// Permissionless once the interval has passed: approves the cranker to pay the vault balance not
+// owed to creators or holder rewards out by weight, less the cranker's bounty. The cranker's
+// transfers have to follow in the same transaction, the bounty first when there is one, then each
+// recipient with a non-zero share in configured order.
+pub fn distribute<'info>(ctx: Context<'_, '_, '_, 'info, Distribute<'info>>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let split = &ctx.accounts.payout_split;
+    require!(
+        now.saturating_sub(split.last_distributed_ts) >= split.interval,
+        TransferHookError::PayoutNotDue
+    );
+
+    // Balances creators haven't claimed yet and unfunded rewards stay in the vault
+    let reserved = vault::reserved_balance(
+        &ctx.accounts.creator_ledger.to_account_info(),
+        &ctx.accounts.rewards_pool.to_account_info(),
+    )?;
+    let total = ctx.accounts.royalty_vault.amount.saturating_sub(reserved);
+    require!(total > 0, TransferHookError::InsufficientVaultBalance);
+    let (rounding, bump) = {
+        let config = ctx.accounts.config.load()?;
+        (config.rounding(), config.vault_authority_bump)
+    };
+    let bounty = match &ctx.accounts.cranker_token_account {
+        Some(_) => fees::bps_fee(total, split.bounty_bps, rounding),
+        None => 0,
+    };
+    let shares = split.shares(total - bounty, rounding);
+
+    let mut legs: Vec<(Pubkey, u64)> = ctx
+        .accounts
+        .cranker_token_account
+        .as_ref()
+        .map(|account| (account.key(), bounty))
+        .into_iter()
+        .collect();
+    legs.extend(
+        split
+            .recipients
+            .iter()
+            .zip(&shares)
+            .map(|(recipient, share)| (recipient.token_account, *share)),
+    );
+
+    let mint_key = ctx.accounts.mint.key();
+    let approved = Outflow {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        source: &ctx.accounts.royalty_vault.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        decimals: ctx.accounts.mint.decimals,
+        owner: &ctx.accounts.vault_authority.to_account_info(),
+        delegate: &ctx.accounts.cranker.to_account_info(),
+        instructions: &ctx.accounts.instructions.to_account_info(),
+    }
+    .approve(&legs, &[&[VAULT_AUTHORITY_SEED, mint_key.as_ref(), &[bump]]])?;
+    accounting::record_vault_flow_if_initialized(
+        &ctx.accounts.royalty_accounting.to_account_info(),
+        &mint_key,
+        VaultFlow::Withdrawal,
+        approved,
+    )?;
+
+    let recipient_count = split.recipients.len();
+    let split = &mut ctx.accounts.payout_split;
+    split.last_distributed_ts = now;
+    split.total_distributed = split.total_distributed.saturating_add(total - bounty);
+    split.total_bounties = split.total_bounties.saturating_add(bounty);
+
+    msg!(
+        "Distributed {} royalty tokens to {} recipients, bounty {}",
+        total - bounty,
+        recipient_count,
+        bounty
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializePayoutSplit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = PayoutSplit::LEN,
+        seeds = [PAYOUT_SPLIT_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub payout_split: Account<'info, PayoutSplit>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePayoutSplit<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [PAYOUT_SPLIT_SEED, config.load()?.mint.as_ref()],
+        bump = payout_split.bump,
+    )]
+    pub payout_split: Account<'info, PayoutSplit>,
+}
+
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    // Anyone, moves the tokens as the vault's delegate
+    pub cranker: Signer<'info>,
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [PAYOUT_SPLIT_SEED, mint.key().as_ref()],
+        bump = payout_split.bump,
+    )]
+    pub payout_split: Account<'info, PayoutSplit>,
+    /// CHECK: PDA owning the royalty vault, signs the approval
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = config.load()?.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: creator ledger PDA, read when initialized so unclaimed balances aren't paid out
+    #[account(
+        seeds = [CREATOR_LEDGER_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub creator_ledger: UncheckedAccount<'info>,
+    /// CHECK: rewards pool PDA, read when initialized so unfunded rewards aren't paid out
+    #[account(
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub rewards_pool: UncheckedAccount<'info>,
+    // Receives the bounty, omit to crank without one
+    #[account(
+        token::mint = mint,
+    )]
+    pub cranker_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, the cranker's transfers are checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
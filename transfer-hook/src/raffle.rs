@@ -0,0 +1,223 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hashv, sysvar};
+use anchor_spl::token_interface::Mint;
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+pub const RAFFLE_DRAW_SEED: &[u8] = b"raffle-draw";
+
+// Keeps the raffle account within the 10KiB a CPI can allocate
+pub const MAX_RAFFLE_ENTRIES: usize = 200;
+
+pub const MAX_RAFFLE_WINNERS: usize = 8;
+
+#[zero_copy]
+pub struct RaffleEntry {
+    pub owner: Pubkey,
+    pub epoch: u64,
+}
+
+// Entries of the open round, one per qualifying transfer to the receiving owner. The hook can't
+// allocate accounts, so entries live in this fixed buffer and qualifying transfers past capacity
+// get no ticket.
+#[account(zero_copy)]
+pub struct Raffle {
+    // Transfers of at least this amount earn a ticket
+    pub threshold: u64,
+    pub round: u64,
+    pub entry_count: u64,
+    pub mint: Pubkey,
+    pub entries: [RaffleEntry; MAX_RAFFLE_ENTRIES],
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl Raffle {
+    pub const LEN: usize = 8 + std::mem::size_of::<Raffle>();
+
+    pub fn enter(&mut self, owner: Pubkey, epoch: u64) -> bool {
+        let index = self.entry_count as usize;
+        if index >= MAX_RAFFLE_ENTRIES {
+            return false;
+        }
+        self.entries[index] = RaffleEntry { owner, epoch };
+        self.entry_count += 1;
+        true
+    }
+}
+
+// Winners of one round. Anyone can recompute them from `slot_hash`, `round` and the round's entries.
+#[account]
+pub struct RaffleDraw {
+    pub mint: Pubkey,
+    pub round: u64,
+    pub slot: u64,
+    pub slot_hash: [u8; 32],
+    pub entry_count: u64,
+    pub winners: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl RaffleDraw {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 32 + 8 + (4 + 32 * MAX_RAFFLE_WINNERS) + 1;
+}
+
+// The hook receives the raffle PDA on every transfer whether or not it exists
+pub fn enter_if_initialized(
+    raffle: &AccountInfo,
+    mint: &Pubkey,
+    owner: Pubkey,
+    amount: u64,
+    epoch: u64,
+) -> Result<()> {
+    if raffle.owner != &crate::ID || raffle.data_len() != Raffle::LEN {
+        return Ok(());
+    }
+
+    let loader = AccountLoader::<Raffle>::try_from(raffle)?;
+    let raffle = &mut loader.load_mut()?;
+    require_keys_eq!(raffle.mint, *mint, TransferHookError::InvalidRaffle);
+    if raffle.threshold == 0 || amount < raffle.threshold {
+        return Ok(());
+    }
+    if raffle.enter(owner, epoch) {
+        verbose_msg!("Raffle ticket {} for {}", raffle.entry_count - 1, owner);
+    }
+
+    Ok(())
+}
+
+pub fn initialize_raffle(ctx: Context<InitializeRaffle>, threshold: u64) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle.load_init()?;
+    raffle.mint = ctx.accounts.mint.key();
+    raffle.threshold = threshold;
+    raffle.bump = ctx.bumps.raffle;
+
+    Ok(())
+}
+
+pub fn set_raffle_threshold(ctx: Context<UpdateRaffle>, threshold: u64) -> Result<()> {
+    ctx.accounts.raffle.load_mut()?.threshold = threshold;
+
+    Ok(())
+}
+
+// Draws winners for the open round from the most recent slot hash, then opens the next round.
+// Tickets are drawn with replacement, an owner holding several tickets can win more than once.
+pub fn draw_raffle(ctx: Context<DrawRaffle>, winner_count: u8) -> Result<()> {
+    require!(
+        winner_count > 0 && winner_count as usize <= MAX_RAFFLE_WINNERS,
+        TransferHookError::InvalidRaffle
+    );
+
+    // SlotHashes data is a u64 length followed by (slot, hash) pairs, newest first
+    let (slot, slot_hash) = {
+        let data = ctx.accounts.slot_hashes.try_borrow_data()?;
+        require!(data.len() >= 48, TransferHookError::InvalidRaffle);
+        let slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let slot_hash: [u8; 32] = data[16..48].try_into().unwrap();
+        (slot, slot_hash)
+    };
+
+    let raffle = &mut ctx.accounts.raffle.load_mut()?;
+    require!(raffle.entry_count > 0, TransferHookError::InvalidRaffle);
+
+    let round = raffle.round.to_le_bytes();
+    let winners = (0..winner_count)
+        .map(|i| {
+            let seed = hashv(&[&slot_hash, &round, &[i]]).to_bytes();
+            let index = u64::from_le_bytes(seed[..8].try_into().unwrap()) % raffle.entry_count;
+            raffle.entries[index as usize].owner
+        })
+        .collect::<Vec<_>>();
+
+    let draw = &mut ctx.accounts.raffle_draw;
+    draw.mint = raffle.mint;
+    draw.round = raffle.round;
+    draw.slot = slot;
+    draw.slot_hash = slot_hash;
+    draw.entry_count = raffle.entry_count;
+    draw.winners = winners;
+    draw.bump = ctx.bumps.raffle_draw;
+
+    msg!(
+        "Drew {} winners from {} tickets in round {}",
+        winner_count,
+        raffle.entry_count,
+        raffle.round
+    );
+
+    raffle.round += 1;
+    raffle.entry_count = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeRaffle<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = Raffle::LEN,
+        seeds = [RAFFLE_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub raffle: AccountLoader<'info, Raffle>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRaffle<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED, config.load()?.mint.as_ref()],
+        bump = raffle.load()?.bump,
+    )]
+    pub raffle: AccountLoader<'info, Raffle>,
+}
+
+#[derive(Accounts)]
+pub struct DrawRaffle<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED, config.load()?.mint.as_ref()],
+        bump = raffle.load()?.bump,
+    )]
+    pub raffle: AccountLoader<'info, Raffle>,
+    #[account(
+        init,
+        payer = authority,
+        space = RaffleDraw::LEN,
+        seeds = [RAFFLE_DRAW_SEED, config.load()?.mint.as_ref(), &raffle.load()?.round.to_le_bytes()],
+        bump
+    )]
+    pub raffle_draw: Account<'info, RaffleDraw>,
+    /// CHECK: SlotHashes sysvar, read raw since it is too large to deserialize on-chain
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,231 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+
+pub const RECEIPT_BOOK_SEED: &[u8] = b"receipt-book";
+pub const RECEIPT_PAGE_SEED: &[u8] = b"receipt-page";
+
+// Byte offset of `ReceiptBook::current_page` in the account data, the meta list derives the
+// current page PDA from it
+pub const RECEIPT_BOOK_CURRENT_PAGE_OFFSET: u8 = 8;
+
+// Keeps pages within the 10KiB a CPI can allocate
+pub const RECEIPTS_PER_PAGE: usize = 128;
+
+// Per-mint index of receipt pages. Pages are opened ahead of time by anyone willing to pay rent,
+// the hook fills them in order and moves on to the next page when one is full.
+#[account(zero_copy)]
+pub struct ReceiptBook {
+    pub current_page: u64,
+    pub page_count: u64,
+    // Receipts ever issued, including dropped ones
+    pub sequence: u64,
+    // Receipts not written because the next page had not been opened yet
+    pub dropped: u64,
+    // Seconds a full page must be kept before its rent can be reclaimed
+    pub archive_window: i64,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl ReceiptBook {
+    pub const LEN: usize = 8 + std::mem::size_of::<ReceiptBook>();
+}
+
+#[zero_copy]
+pub struct RoyaltyReceipt {
+    pub sequence: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub slot: u64,
+    pub payer: Pubkey,
+}
+
+#[account(zero_copy)]
+pub struct ReceiptPage {
+    pub page_index: u64,
+    pub entry_count: u64,
+    // Time of the last receipt written, the archive window runs from here
+    pub last_ts: i64,
+    pub mint: Pubkey,
+    // Refunded when the page is closed
+    pub rent_payer: Pubkey,
+    pub receipts: [RoyaltyReceipt; RECEIPTS_PER_PAGE],
+}
+
+impl ReceiptPage {
+    pub const LEN: usize = 8 + std::mem::size_of::<ReceiptPage>();
+}
+
+// The hook receives the book and its current page whether or not receipts are enabled
+pub fn record_if_initialized(
+    receipt_book: &AccountInfo,
+    receipt_page: &AccountInfo,
+    mint: &Pubkey,
+    mut receipt: RoyaltyReceipt,
+    now: i64,
+) -> Result<()> {
+    if receipt.fee == 0 || receipt_book.owner != &crate::ID || receipt_book.data_len() != ReceiptBook::LEN {
+        return Ok(());
+    }
+
+    let book_loader = AccountLoader::<ReceiptBook>::try_from(receipt_book)?;
+    let book = &mut book_loader.load_mut()?;
+    require_keys_eq!(book.mint, *mint, TransferHookError::InvalidReceiptAccount);
+    receipt.sequence = book.sequence;
+    book.sequence += 1;
+
+    if receipt_page.owner != &crate::ID || receipt_page.data_len() != ReceiptPage::LEN {
+        book.dropped += 1;
+        verbose_msg!("No receipt page open, dropped receipt {}", receipt.sequence);
+        return Ok(());
+    }
+
+    let page_loader = AccountLoader::<ReceiptPage>::try_from(receipt_page)?;
+    let page = &mut page_loader.load_mut()?;
+    require!(
+        page.mint == *mint && page.page_index == book.current_page,
+        TransferHookError::InvalidReceiptAccount
+    );
+    page.receipts[page.entry_count as usize] = receipt;
+    page.entry_count += 1;
+    page.last_ts = now;
+    if page.entry_count as usize == RECEIPTS_PER_PAGE {
+        book.current_page += 1;
+    }
+
+    Ok(())
+}
+
+pub fn initialize_receipt_book(ctx: Context<InitializeReceiptBook>, archive_window: i64) -> Result<()> {
+    require!(archive_window >= 0, TransferHookError::InvalidCooldown);
+
+    let book = &mut ctx.accounts.receipt_book.load_init()?;
+    book.mint = ctx.accounts.mint.key();
+    book.archive_window = archive_window;
+    book.bump = ctx.bumps.receipt_book;
+
+    Ok(())
+}
+
+pub fn set_receipt_archive_window(ctx: Context<UpdateReceiptBook>, archive_window: i64) -> Result<()> {
+    require!(archive_window >= 0, TransferHookError::InvalidCooldown);
+    ctx.accounts.receipt_book.load_mut()?.archive_window = archive_window;
+
+    Ok(())
+}
+
+// Permissionless: opens the next page so the hook never runs out of room
+pub fn open_receipt_page(ctx: Context<OpenReceiptPage>) -> Result<()> {
+    let book = &mut ctx.accounts.receipt_book.load_mut()?;
+    let page = &mut ctx.accounts.receipt_page.load_init()?;
+    page.page_index = book.page_count;
+    page.mint = book.mint;
+    page.rent_payer = ctx.accounts.payer.key();
+    book.page_count += 1;
+
+    msg!("Opened receipt page {}", page.page_index);
+
+    Ok(())
+}
+
+// Permissionless: closes a full page once the archive window has passed, refunding its opener
+pub fn close_receipt_page(ctx: Context<CloseReceiptPage>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let archive_window = ctx.accounts.receipt_book.load()?.archive_window;
+    let page = ctx.accounts.receipt_page.load()?;
+
+    require!(
+        page.entry_count as usize == RECEIPTS_PER_PAGE
+            && now.saturating_sub(page.last_ts) >= archive_window,
+        TransferHookError::ReceiptPageNotArchivable
+    );
+    msg!("Closed receipt page {}", page.page_index);
+    drop(page);
+
+    ctx.accounts
+        .receipt_page
+        .close(ctx.accounts.rent_payer.to_account_info())
+}
+
+#[derive(Accounts)]
+pub struct InitializeReceiptBook<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = ReceiptBook::LEN,
+        seeds = [RECEIPT_BOOK_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub receipt_book: AccountLoader<'info, ReceiptBook>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateReceiptBook<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_BOOK_SEED, config.load()?.mint.as_ref()],
+        bump = receipt_book.load()?.bump,
+    )]
+    pub receipt_book: AccountLoader<'info, ReceiptBook>,
+}
+
+#[derive(Accounts)]
+pub struct OpenReceiptPage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub receipt_book: AccountLoader<'info, ReceiptBook>,
+    #[account(
+        init,
+        payer = payer,
+        space = ReceiptPage::LEN,
+        seeds = [
+            RECEIPT_PAGE_SEED,
+            receipt_book.load()?.mint.as_ref(),
+            &receipt_book.load()?.page_count.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub receipt_page: AccountLoader<'info, ReceiptPage>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReceiptPage<'info> {
+    #[account(
+        has_one = mint @ TransferHookError::InvalidReceiptAccount,
+    )]
+    pub receipt_book: AccountLoader<'info, ReceiptBook>,
+    /// CHECK: only used for its key in the seeds
+    pub mint: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_PAGE_SEED, mint.key().as_ref(), &receipt_page.load()?.page_index.to_le_bytes()],
+        bump,
+        has_one = rent_payer @ TransferHookError::InvalidRentDestination,
+    )]
+    pub receipt_page: AccountLoader<'info, ReceiptPage>,
+    /// CHECK: receives the page rent, checked against the page
+    #[account(mut)]
+    pub rent_payer: UncheckedAccount<'info>,
+}
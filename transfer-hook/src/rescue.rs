@@ -0,0 +1,291 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+use crate::outflow::Outflow;
+use crate::rewards::{RewardsPool, REWARDS_POOL_SEED};
+use crate::vault::VAULT_AUTHORITY_SEED;
+use crate::wrapper::{Wrapper, WRAPPER_AUTHORITY_SEED, WRAPPER_SEED};
+
+pub const PENDING_RESCUE_SEED: &[u8] = b"pending-rescue";
+
+// Seconds between proposing a rescue and executing it
+pub const RESCUE_TIMELOCK: i64 = 72 * 60 * 60;
+
+// Program PDA owning the token account tokens are rescued from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RescueSource {
+    VaultAuthority,
+    RewardsPool,
+    WrapperAuthority,
+}
+
+#[account]
+pub struct PendingRescue {
+    pub mint: Pubkey,
+    pub source: RescueSource,
+    pub token_account: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub proposed_at: i64,
+    pub bump: u8,
+}
+
+impl PendingRescue {
+    pub const LEN: usize = 8 + 32 + 1 + 32 + 32 + 8 + 8 + 1;
+}
+
+#[event]
+pub struct RescueProposed {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct RescueExecuted {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RescueCancelled {
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+}
+
+impl<'info> RescueSourceAccounts<'info> {
+    // Seed and bump of the PDA owning the token account, and how much of its balance the program
+    // doesn't account for. The royalty vault is never a source, it has its own withdrawal paths.
+    fn rescuable(&self, source: RescueSource) -> Result<(&'static [u8], u8, u64)> {
+        let config = RoyaltyConfig::load_current(&self.config)?;
+        let token_account = &self.token_account;
+        let (seed, bump, reserved) = match source {
+            RescueSource::VaultAuthority => {
+                require_keys_neq!(
+                    token_account.key(),
+                    config.royalty_vault,
+                    TransferHookError::InvalidRescueSource
+                );
+                (VAULT_AUTHORITY_SEED, config.vault_authority_bump, 0)
+            }
+            RescueSource::RewardsPool => {
+                let pool = self
+                    .rewards_pool
+                    .as_ref()
+                    .ok_or(TransferHookError::InvalidRescueSource)?
+                    .load()?;
+                // Funded rewards not yet claimed are owed to holders
+                let reserved = if token_account.key() == pool.pool_token_account {
+                    pool.total_funded.saturating_sub(pool.total_claimed)
+                } else {
+                    0
+                };
+                (REWARDS_POOL_SEED, pool.bump, reserved)
+            }
+            RescueSource::WrapperAuthority => {
+                let wrapper = self.wrapper.as_ref().ok_or(TransferHookError::InvalidRescueSource)?;
+                // Legacy tokens backing the hooked supply stay locked
+                let reserved = if token_account.key() == wrapper.legacy_vault {
+                    self.mint.supply
+                } else {
+                    0
+                };
+                (WRAPPER_AUTHORITY_SEED, wrapper.authority_bump, reserved)
+            }
+        };
+
+        let owner = Pubkey::create_program_address(&[seed, config.mint.as_ref(), &[bump]], &crate::ID)
+            .map_err(|_| TransferHookError::InvalidRescueSource)?;
+        require_keys_eq!(token_account.owner, owner, TransferHookError::InvalidRescueSource);
+        require_keys_eq!(self.token_owner.key(), owner, TransferHookError::InvalidRescueSource);
+
+        Ok((seed, bump, token_account.amount.saturating_sub(reserved)))
+    }
+}
+
+// Queues a rescue of tokens stranded in a program PDA's token account, executable by the admin
+// once the timelock has passed
+pub fn propose_rescue(ctx: Context<ProposeRescue>, source: RescueSource, amount: u64) -> Result<()> {
+    let (_, _, rescuable) = ctx.accounts.source.rescuable(source)?;
+    require!(
+        amount > 0 && amount <= rescuable,
+        TransferHookError::RescueExceedsSurplus
+    );
+
+    let pending = &mut ctx.accounts.pending_rescue;
+    pending.mint = ctx.accounts.source.mint.key();
+    pending.source = source;
+    pending.token_account = ctx.accounts.source.token_account.key();
+    pending.destination = ctx.accounts.destination.key();
+    pending.amount = amount;
+    pending.proposed_at = Clock::get()?.unix_timestamp;
+    pending.bump = ctx.bumps.pending_rescue;
+
+    emit!(RescueProposed {
+        mint: pending.mint,
+        token_account: pending.token_account,
+        destination: pending.destination,
+        amount,
+        executable_at: pending.proposed_at.saturating_add(RESCUE_TIMELOCK),
+    });
+
+    Ok(())
+}
+
+// Approves the authority for the rescued amount, the authority's transfer to the destination has
+// to follow in the same transaction
+pub fn execute_rescue<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteRescue<'info>>) -> Result<()> {
+    let pending = &ctx.accounts.pending_rescue;
+    require!(
+        Clock::get()?.unix_timestamp.saturating_sub(pending.proposed_at) >= RESCUE_TIMELOCK,
+        TransferHookError::RescueNotReady
+    );
+    // Accounting may have changed while the rescue was queued
+    let (seed, bump, rescuable) = ctx.accounts.source.rescuable(pending.source)?;
+    require!(
+        pending.amount <= rescuable,
+        TransferHookError::RescueExceedsSurplus
+    );
+
+    let mint_key = ctx.accounts.source.mint.key();
+    Outflow {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        source: &ctx.accounts.source.token_account.to_account_info(),
+        mint: &ctx.accounts.token_mint.to_account_info(),
+        decimals: ctx.accounts.token_mint.decimals,
+        owner: &ctx.accounts.source.token_owner.to_account_info(),
+        delegate: &ctx.accounts.authority.to_account_info(),
+        instructions: &ctx.accounts.instructions.to_account_info(),
+    }
+    .approve(
+        &[(pending.destination, pending.amount)],
+        &[&[seed, mint_key.as_ref(), &[bump]]],
+    )?;
+
+    emit!(RescueExecuted {
+        mint: pending.mint,
+        token_account: pending.token_account,
+        destination: pending.destination,
+        amount: pending.amount,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_rescue(ctx: Context<CancelRescue>) -> Result<()> {
+    let pending = &ctx.accounts.pending_rescue;
+    emit!(RescueCancelled {
+        mint: pending.mint,
+        token_account: pending.token_account,
+        amount: pending.amount,
+    });
+
+    Ok(())
+}
+
+// Token account to rescue from and the accounting it is checked against
+#[derive(Accounts)]
+pub struct RescueSourceAccounts<'info> {
+    #[account(
+        mut,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: program PDA owning the token account, derivation checked in the handler
+    pub token_owner: UncheckedAccount<'info>,
+    // Required when rescuing from the rewards pool
+    #[account(
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump = rewards_pool.load()?.bump,
+    )]
+    pub rewards_pool: Option<AccountLoader<'info, RewardsPool>>,
+    // Required when rescuing from the wrapper authority
+    #[account(
+        seeds = [WRAPPER_SEED, mint.key().as_ref()],
+        bump = wrapper.bump,
+    )]
+    pub wrapper: Option<Account<'info, Wrapper>>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRescue<'info> {
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&source.config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+    pub source: RescueSourceAccounts<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = PendingRescue::LEN,
+        seeds = [PENDING_RESCUE_SEED, source.mint.key().as_ref(), source.token_account.key().as_ref()],
+        bump
+    )]
+    pub pending_rescue: Account<'info, PendingRescue>,
+    #[account(
+        token::mint = source.token_account.mint,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRescue<'info> {
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&source.config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+    pub source: RescueSourceAccounts<'info>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_RESCUE_SEED, source.mint.key().as_ref(), source.token_account.key().as_ref()],
+        bump = pending_rescue.bump,
+        has_one = destination,
+    )]
+    pub pending_rescue: Account<'info, PendingRescue>,
+    // Mint of the rescued tokens, not necessarily the hooked mint
+    #[account(
+        address = source.token_account.mint,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: instructions sysvar, the authority's transfer is checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRescue<'info> {
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == pending_rescue.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PENDING_RESCUE_SEED, pending_rescue.mint.as_ref(), pending_rescue.token_account.as_ref()],
+        bump = pending_rescue.bump,
+    )]
+    pub pending_rescue: Account<'info, PendingRescue>,
+}
@@ -0,0 +1,428 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::accounting::{self, VaultFlow, ROYALTY_ACCOUNTING_SEED};
+use crate::config::{RoyaltyConfig, LOCK_RECIPIENTS};
+use crate::error::TransferHookError;
+use crate::extensions;
+use crate::fees::{self, RoundingMode, BPS_DENOMINATOR};
+use crate::ledger::{self, CREATOR_LEDGER_SEED};
+use crate::outflow::Outflow;
+use crate::snapshot::{HolderSnapshot, HOLDER_SNAPSHOT_SEED};
+use crate::vault::VAULT_AUTHORITY_SEED;
+
+pub const REWARDS_POOL_SEED: &[u8] = b"rewards-pool";
+pub const REWARDS_EPOCH_SEED: &[u8] = b"rewards-epoch";
+pub const REWARDS_CLAIM_SEED: &[u8] = b"rewards-claim";
+
+// Holder rewards funded by a share of royalties. The hook accrues the share as `pending`, the
+// tokens stay in the royalty vault until `fund_epoch` moves them to the pool's token account.
+#[account(zero_copy)]
+pub struct RewardsPool {
+    pub pending: u64,
+    pub epoch_count: u64,
+    pub total_funded: u64,
+    pub total_claimed: u64,
+    pub mint: Pubkey,
+    // ATA of this PDA, claims are paid from it
+    pub pool_token_account: Pubkey,
+    pub share_bps: u16,
+    pub bump: u8,
+    pub _padding: [u8; 5],
+}
+
+impl RewardsPool {
+    pub const LEN: usize = 8 + std::mem::size_of::<RewardsPool>();
+}
+
+// One funding round, claimable pro-rata against balances at `snapshot_id`
+#[account]
+pub struct RewardsEpoch {
+    pub mint: Pubkey,
+    pub epoch_id: u64,
+    pub snapshot_id: u64,
+    pub amount: u64,
+    // Supply at the snapshot, excluding the vault and the pool itself
+    pub eligible_supply: u64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl RewardsEpoch {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+}
+
+// Existence marks the token account's claim for an epoch as paid
+#[account]
+pub struct RewardsClaim {
+    pub epoch: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl RewardsClaim {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1;
+}
+
+// The hook receives the pool PDA on every transfer whether or not it exists. Returns the share
+// set aside, zero when the mint has no pool.
+pub fn accrue_if_initialized(
+    rewards_pool: &AccountInfo,
+    mint: &Pubkey,
+    royalty: u64,
+    rounding: RoundingMode,
+) -> Result<u64> {
+    if royalty == 0 || rewards_pool.owner != &crate::ID || rewards_pool.data_len() != RewardsPool::LEN {
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<RewardsPool>::try_from(rewards_pool)?;
+    let pool = &mut loader.load_mut()?;
+    require_keys_eq!(pool.mint, *mint, TransferHookError::InvalidRewardsPool);
+    let share = fees::bps_fee(royalty, pool.share_bps, rounding);
+    pool.pending = pool.pending.saturating_add(share);
+
+    Ok(share)
+}
+
+// Rewards accrued in the vault but not yet funded, zero when the mint has no pool
+pub fn pending_if_initialized(rewards_pool: &AccountInfo) -> Result<u64> {
+    if rewards_pool.owner != &crate::ID || rewards_pool.data_len() != RewardsPool::LEN {
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<RewardsPool>::try_from(rewards_pool)?;
+    let pending = loader.load()?.pending;
+    Ok(pending)
+}
+
+// Share of vault royalties set aside for holders, zero when the mint has no pool
+pub fn share_bps_if_initialized(rewards_pool: &AccountInfo) -> Result<u16> {
+    if rewards_pool.owner != &crate::ID || rewards_pool.data_len() != RewardsPool::LEN {
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<RewardsPool>::try_from(rewards_pool)?;
+    let share_bps = loader.load()?.share_bps;
+    Ok(share_bps)
+}
+
+pub fn initialize_rewards_pool(ctx: Context<InitializeRewardsPool>, share_bps: u16) -> Result<()> {
+    require!(share_bps as u64 <= BPS_DENOMINATOR, TransferHookError::InvalidBps);
+    ledger::require_royalty_split(
+        ledger::total_weight_if_initialized(&ctx.accounts.creator_ledger.to_account_info())?,
+        share_bps,
+    )?;
+
+    let pool = &mut ctx.accounts.rewards_pool.load_init()?;
+    pool.mint = ctx.accounts.mint.key();
+    pool.pool_token_account = ctx.accounts.pool_token_account.key();
+    pool.share_bps = share_bps;
+    pool.bump = ctx.bumps.rewards_pool;
+
+    Ok(())
+}
+
+pub fn set_rewards_share(ctx: Context<UpdateRewardsPool>, share_bps: u16) -> Result<()> {
+    require!(share_bps as u64 <= BPS_DENOMINATOR, TransferHookError::InvalidBps);
+    RoyaltyConfig::load_current(&ctx.accounts.config)?.require_unlocked(LOCK_RECIPIENTS)?;
+    ledger::require_royalty_split(
+        ledger::total_weight_if_initialized(&ctx.accounts.creator_ledger.to_account_info())?,
+        share_bps,
+    )?;
+    ctx.accounts.rewards_pool.load_mut()?.share_bps = share_bps;
+
+    Ok(())
+}
+
+// Approves the authority to move pending rewards from the vault into the pool and takes a snapshot
+// to claim against. The authority's transfer to the pool has to follow in the same transaction.
+pub fn fund_epoch<'info>(ctx: Context<'_, '_, '_, 'info, FundEpoch<'info>>) -> Result<()> {
+    let pending = ctx.accounts.rewards_pool.load()?.pending;
+    require!(
+        pending > 0 && pending <= ctx.accounts.royalty_vault.amount,
+        TransferHookError::InsufficientVaultBalance
+    );
+
+    let mint_key = ctx.accounts.mint.key();
+    let bump = ctx.accounts.config.load()?.vault_authority_bump;
+    Outflow {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        source: &ctx.accounts.royalty_vault.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        decimals: ctx.accounts.mint.decimals,
+        owner: &ctx.accounts.vault_authority.to_account_info(),
+        delegate: &ctx.accounts.authority.to_account_info(),
+        instructions: &ctx.accounts.instructions.to_account_info(),
+    }
+    .approve(
+        &[(ctx.accounts.pool_token_account.key(), pending)],
+        &[&[VAULT_AUTHORITY_SEED, mint_key.as_ref(), &[bump]]],
+    )?;
+    accounting::record_vault_flow_if_initialized(
+        &ctx.accounts.royalty_accounting.to_account_info(),
+        &mint_key,
+        VaultFlow::Withdrawal,
+        pending,
+    )?;
+
+    // The transfer lands after this instruction, so balances are projected. Transfer fees withheld
+    // on the way in reduce what the pool receives.
+    let epoch_now = Clock::get()?.epoch;
+    let funded = pending.saturating_sub(extensions::transfer_fee_extension_fee(
+        &ctx.accounts.mint.to_account_info(),
+        pending,
+        epoch_now,
+    )?);
+    let eligible_supply = ctx
+        .accounts
+        .mint
+        .supply
+        .saturating_sub(ctx.accounts.royalty_vault.amount - pending)
+        .saturating_sub(ctx.accounts.pool_token_account.amount.saturating_add(funded));
+    require!(eligible_supply > 0, TransferHookError::InvalidRewardsPool);
+
+    let snapshot_id = {
+        let config = &mut ctx.accounts.config.load_mut()?;
+        config.current_snapshot_id += 1;
+        config.last_snapshot_epoch = epoch_now;
+        config.current_snapshot_id
+    };
+
+    let pool = &mut ctx.accounts.rewards_pool.load_mut()?;
+    let epoch = &mut ctx.accounts.rewards_epoch;
+    epoch.mint = mint_key;
+    epoch.epoch_id = pool.epoch_count;
+    epoch.snapshot_id = snapshot_id;
+    epoch.amount = funded;
+    epoch.eligible_supply = eligible_supply;
+    epoch.bump = ctx.bumps.rewards_epoch;
+
+    pool.pending = 0;
+    pool.epoch_count += 1;
+    pool.total_funded = pool.total_funded.saturating_add(funded);
+
+    msg!(
+        "Funded rewards epoch {} with {} tokens at snapshot {}",
+        epoch.epoch_id,
+        funded,
+        snapshot_id
+    );
+
+    Ok(())
+}
+
+// Approves the owner for the token account's share of the epoch, pro-rata to its balance at the
+// epoch's snapshot. The owner's transfer out of the pool has to follow in the same transaction.
+pub fn claim_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>) -> Result<()> {
+    let epoch = &mut ctx.accounts.rewards_epoch;
+    let balance = ctx
+        .accounts
+        .holder_snapshot
+        .load()?
+        .balance_at(epoch.snapshot_id, ctx.accounts.token_account.amount)?;
+    // Rounded per the config, capped so claims never exceed what the epoch was funded with
+    let rounding = RoyaltyConfig::load_current(&ctx.accounts.config)?.rounding();
+    let payout = fees::mul_div(epoch.amount, balance, epoch.eligible_supply, rounding)
+        .min(epoch.amount.saturating_sub(epoch.claimed));
+
+    if payout > 0 {
+        let mint_key = ctx.accounts.mint.key();
+        let bump = ctx.accounts.rewards_pool.load()?.bump;
+        Outflow {
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            source: &ctx.accounts.pool_token_account.to_account_info(),
+            mint: &ctx.accounts.mint.to_account_info(),
+            decimals: ctx.accounts.mint.decimals,
+            owner: &ctx.accounts.rewards_pool.to_account_info(),
+            delegate: &ctx.accounts.owner.to_account_info(),
+            instructions: &ctx.accounts.instructions.to_account_info(),
+        }
+        .approve(
+            &[(ctx.accounts.token_account.key(), payout)],
+            &[&[REWARDS_POOL_SEED, mint_key.as_ref(), &[bump]]],
+        )?;
+    }
+
+    epoch.claimed = epoch.claimed.saturating_add(payout);
+    let pool = &mut ctx.accounts.rewards_pool.load_mut()?;
+    pool.total_claimed = pool.total_claimed.saturating_add(payout);
+
+    let claim = &mut ctx.accounts.rewards_claim;
+    claim.epoch = epoch.key();
+    claim.token_account = ctx.accounts.token_account.key();
+    claim.amount = payout;
+    claim.bump = ctx.bumps.rewards_claim;
+
+    msg!("Claimed {} rewards from epoch {}", payout, epoch.epoch_id);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardsPool<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = RewardsPool::LEN,
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub rewards_pool: AccountLoader<'info, RewardsPool>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = rewards_pool,
+        associated_token::token_program = token_program,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: creator ledger PDA, may be uninitialized, its weights are checked against the share
+    #[account(
+        seeds = [CREATOR_LEDGER_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub creator_ledger: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRewardsPool<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [REWARDS_POOL_SEED, config.load()?.mint.as_ref()],
+        bump = rewards_pool.load()?.bump,
+    )]
+    pub rewards_pool: AccountLoader<'info, RewardsPool>,
+    /// CHECK: creator ledger PDA, may be uninitialized, its weights are checked against the share
+    #[account(
+        seeds = [CREATOR_LEDGER_SEED, config.load()?.mint.as_ref()],
+        bump,
+    )]
+    pub creator_ledger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundEpoch<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump = rewards_pool.load()?.bump,
+    )]
+    pub rewards_pool: AccountLoader<'info, RewardsPool>,
+    #[account(
+        init,
+        payer = authority,
+        space = RewardsEpoch::LEN,
+        seeds = [REWARDS_EPOCH_SEED, mint.key().as_ref(), &rewards_pool.load()?.epoch_count.to_le_bytes()],
+        bump
+    )]
+    pub rewards_epoch: Account<'info, RewardsEpoch>,
+    /// CHECK: PDA owning the royalty vault, signs the approval
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = config.load()?.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        address = rewards_pool.load()?.pool_token_account,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: instructions sysvar, the authority's transfer is checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump = rewards_pool.load()?.bump,
+    )]
+    pub rewards_pool: AccountLoader<'info, RewardsPool>,
+    #[account(
+        mut,
+        seeds = [REWARDS_EPOCH_SEED, mint.key().as_ref(), &rewards_epoch.epoch_id.to_le_bytes()],
+        bump = rewards_epoch.bump,
+    )]
+    pub rewards_epoch: Account<'info, RewardsEpoch>,
+    #[account(
+        token::mint = mint,
+        token::authority = owner,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [HOLDER_SNAPSHOT_SEED, mint.key().as_ref(), token_account.key().as_ref()],
+        bump = holder_snapshot.load()?.bump,
+    )]
+    pub holder_snapshot: AccountLoader<'info, HolderSnapshot>,
+    #[account(
+        init,
+        payer = owner,
+        space = RewardsClaim::LEN,
+        seeds = [REWARDS_CLAIM_SEED, rewards_epoch.key().as_ref(), token_account.key().as_ref()],
+        bump
+    )]
+    pub rewards_claim: Account<'info, RewardsClaim>,
+    #[account(
+        mut,
+        address = rewards_pool.load()?.pool_token_account,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: instructions sysvar, the owner's transfer is checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::config::{RoyaltyConfig, UpdateConfig, LOCK_RECIPIENTS};
+use crate::error::TransferHookError;
+
+pub const FEE_ACCRUAL_SEED: &[u8] = b"fee-accrual";
+
+// Royalties owed by a token account. Token2022 moves the full amount before the hook runs and the
+// hook can't move tokens back through it, so each charged royalty is added to `owed` and the
+// owner pays it by transferring to the royalty recipient, which the hook counts against `owed`.
+// Token accounts need one before any transfer that is charged.
+#[account(zero_copy)]
+pub struct FeeAccrual {
+    pub owed: u64,
+    pub total_accrued: u64,
+    pub total_settled: u64,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl FeeAccrual {
+    pub const LEN: usize = 8 + std::mem::size_of::<FeeAccrual>();
+
+    // `payment` is what this transfer moved to the royalty recipient, returns how much of it paid
+    // earlier accruals. Without lazy settlement everything owed has to be paid before the next
+    // transfer elsewhere, with it royalties may add up as long as the balance covers them.
+    pub fn accrue_or_settle(
+        &mut self,
+        lazy: bool,
+        remaining_balance: u64,
+        fee: u64,
+        payment: u64,
+    ) -> Result<u64> {
+        let settled = payment.min(self.owed);
+        self.owed -= settled;
+        self.total_settled = self.total_settled.saturating_add(settled);
+
+        require!(
+            lazy || payment > 0 || self.owed == 0,
+            TransferHookError::RoyaltiesUnsettled
+        );
+        self.owed = self.owed.saturating_add(fee);
+        self.total_accrued = self.total_accrued.saturating_add(fee);
+        require!(
+            remaining_balance >= self.owed,
+            TransferHookError::RoyaltiesExceedBalance
+        );
+
+        Ok(settled)
+    }
+}
+
+// The hook receives the accrual PDA on every transfer whether or not it exists. Transfers that
+// aren't charged and pay nothing pass without one.
+pub fn accrue_or_settle(
+    fee_accrual: &AccountInfo,
+    source_token: &Pubkey,
+    config: &RoyaltyConfig,
+    remaining_balance: u64,
+    fee: u64,
+    payment: u64,
+) -> Result<u64> {
+    if fee_accrual.owner != &crate::ID || fee_accrual.data_len() != FeeAccrual::LEN {
+        require!(fee == 0, TransferHookError::FeeAccrualRequired);
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<FeeAccrual>::try_from(fee_accrual)?;
+    let accrual = &mut loader.load_mut()?;
+    require_keys_eq!(
+        accrual.mint,
+        config.mint,
+        TransferHookError::InvalidFeeAccrual
+    );
+    require_keys_eq!(
+        accrual.token_account,
+        *source_token,
+        TransferHookError::InvalidFeeAccrual
+    );
+
+    accrual.accrue_or_settle(config.lazy_settlement != 0, remaining_balance, fee, payment)
+}
+
+// Royalties the token account still owes, zero without an accrual
+pub fn owed_if_initialized(fee_accrual: &AccountInfo) -> Result<u64> {
+    if fee_accrual.owner != &crate::ID || fee_accrual.data_len() != FeeAccrual::LEN {
+        return Ok(0);
+    }
+
+    let loader = AccountLoader::<FeeAccrual>::try_from(fee_accrual)?;
+    let owed = loader.load()?.owed;
+    Ok(owed)
+}
+
+// Permissionless, whoever creates it pays the rent
+pub fn initialize_fee_accrual(ctx: Context<InitializeFeeAccrual>) -> Result<()> {
+    let accrual = &mut ctx.accounts.fee_accrual.load_init()?;
+    accrual.token_account = ctx.accounts.token_account.key();
+    accrual.mint = ctx.accounts.mint.key();
+    accrual.bump = ctx.bumps.fee_accrual;
+
+    Ok(())
+}
+
+pub fn set_lazy_settlement(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_RECIPIENTS)?;
+    config.lazy_settlement = enabled as u8;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeAccrual<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        token::mint = mint,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = FeeAccrual::LEN,
+        seeds = [FEE_ACCRUAL_SEED, token_account.key().as_ref()],
+        bump
+    )]
+    pub fee_accrual: AccountLoader<'info, FeeAccrual>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    #[test]
+    fn royalty_is_owed_until_paid() {
+        let mut accrual = FeeAccrual::zeroed();
+        assert_eq!(accrual.accrue_or_settle(false, 1_000, 50, 0).unwrap(), 0);
+        assert_eq!(accrual.owed, 50);
+
+        // Paying the recipient more than owed settles the accrual only
+        assert_eq!(accrual.accrue_or_settle(false, 950, 0, 80).unwrap(), 50);
+        assert_eq!(accrual.owed, 0);
+        assert_eq!(accrual.total_accrued, 50);
+        assert_eq!(accrual.total_settled, 50);
+    }
+
+    #[test]
+    fn unpaid_royalty_blocks_transfers_without_lazy_settlement() {
+        let mut accrual = FeeAccrual::zeroed();
+        accrual.accrue_or_settle(false, 1_000, 50, 0).unwrap();
+
+        assert!(accrual.accrue_or_settle(false, 900, 0, 0).is_err());
+        assert!(accrual.accrue_or_settle(true, 900, 10, 0).is_ok());
+        assert_eq!(accrual.owed, 60);
+    }
+
+    #[test]
+    fn owed_royalties_must_stay_covered_by_the_balance() {
+        assert!(FeeAccrual::zeroed().accrue_or_settle(true, 40, 50, 0).is_err());
+        assert!(FeeAccrual::zeroed().accrue_or_settle(true, 50, 50, 0).is_ok());
+    }
+}
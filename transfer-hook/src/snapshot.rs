@@ -0,0 +1,233 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::config::{RoyaltyConfig, LOCK_RESTRICTIONS};
+use crate::error::TransferHookError;
+use crate::extensions;
+
+pub const HOLDER_SNAPSHOT_SEED: &[u8] = b"holder-snapshot";
+
+pub const MAX_SNAPSHOT_ENTRIES: usize = 16;
+
+#[zero_copy]
+pub struct SnapshotEntry {
+    pub snapshot_id: u64,
+    pub balance: u64,
+}
+
+// Balance history of one token account. Before the first change after a snapshot is taken, the
+// hook records the balance as of that snapshot. A snapshot with no recorded entry at or after it
+// means the balance hasn't changed since, so the current balance applies.
+#[account(zero_copy)]
+pub struct HolderSnapshot {
+    // Entries ever recorded, the ring keeps the latest `MAX_SNAPSHOT_ENTRIES`
+    pub total_entries: u64,
+    // Snapshots taken before this account existed can't be answered
+    pub created_at_snapshot: u64,
+    pub entries: [SnapshotEntry; MAX_SNAPSHOT_ENTRIES],
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl HolderSnapshot {
+    pub const LEN: usize = 8 + std::mem::size_of::<HolderSnapshot>();
+
+    fn entry(&self, sequence: u64) -> &SnapshotEntry {
+        &self.entries[(sequence % MAX_SNAPSHOT_ENTRIES as u64) as usize]
+    }
+
+    fn latest_snapshot_id(&self) -> Option<u64> {
+        (self.total_entries > 0).then(|| self.entry(self.total_entries - 1).snapshot_id)
+    }
+
+    // Record `balance_before` for the current snapshot unless this account already changed since
+    pub fn record_before_change(&mut self, current_snapshot_id: u64, balance_before: u64) {
+        if current_snapshot_id == 0 || self.latest_snapshot_id() >= Some(current_snapshot_id) {
+            return;
+        }
+        let index = (self.total_entries % MAX_SNAPSHOT_ENTRIES as u64) as usize;
+        self.entries[index] = SnapshotEntry {
+            snapshot_id: current_snapshot_id,
+            balance: balance_before,
+        };
+        self.total_entries += 1;
+    }
+
+    pub fn balance_at(&self, snapshot_id: u64, current_balance: u64) -> Result<u64> {
+        require!(
+            snapshot_id > self.created_at_snapshot,
+            TransferHookError::SnapshotUnavailable
+        );
+
+        let retained = self.total_entries.min(MAX_SNAPSHOT_ENTRIES as u64);
+        let oldest = self.total_entries - retained;
+        // Entries are in increasing snapshot order, the first at or after the query holds the answer
+        for sequence in oldest..self.total_entries {
+            let entry = self.entry(sequence);
+            if entry.snapshot_id >= snapshot_id {
+                // An overwritten entry between the query and this one would have been the answer
+                require!(
+                    oldest == 0 || sequence > oldest || entry.snapshot_id == snapshot_id,
+                    TransferHookError::SnapshotUnavailable
+                );
+                return Ok(entry.balance);
+            }
+        }
+        Ok(current_balance)
+    }
+}
+
+// The hook receives snapshot PDAs for both token accounts whether or not they exist
+pub fn record_if_initialized(
+    holder_snapshot: &AccountInfo,
+    token_account: &Pubkey,
+    current_snapshot_id: u64,
+    balance_before: u64,
+) -> Result<()> {
+    if current_snapshot_id == 0
+        || holder_snapshot.owner != &crate::ID
+        || holder_snapshot.data_len() != HolderSnapshot::LEN
+    {
+        return Ok(());
+    }
+
+    let loader = AccountLoader::<HolderSnapshot>::try_from(holder_snapshot)?;
+    let snapshot = &mut loader.load_mut()?;
+    require_keys_eq!(
+        snapshot.token_account,
+        *token_account,
+        TransferHookError::InvalidHolderState
+    );
+    snapshot.record_before_change(current_snapshot_id, balance_before);
+
+    Ok(())
+}
+
+// Records both sides of a transfer token2022 has already applied, reconstructing the balances
+// from before it. Only trusted while the source's `transferring` flag is set, otherwise `amount`
+// is whatever the caller claims.
+pub fn record_transfer_if_initialized(
+    source_snapshot: &AccountInfo,
+    source_token: &InterfaceAccount<TokenAccount>,
+    destination_snapshot: &AccountInfo,
+    destination_token: &InterfaceAccount<TokenAccount>,
+    current_snapshot_id: u64,
+    amount: u64,
+) -> Result<()> {
+    if current_snapshot_id == 0 || !extensions::is_transferring(&source_token.to_account_info())? {
+        return Ok(());
+    }
+
+    record_if_initialized(
+        source_snapshot,
+        &source_token.key(),
+        current_snapshot_id,
+        source_token.amount.saturating_add(amount),
+    )?;
+    record_if_initialized(
+        destination_snapshot,
+        &destination_token.key(),
+        current_snapshot_id,
+        destination_token.amount.saturating_sub(amount),
+    )
+}
+
+// Authority at any time, or anyone once per epoch when the crank is enabled
+pub fn take_snapshot(ctx: Context<TakeSnapshot>) -> Result<()> {
+    let epoch = Clock::get()?.epoch;
+    let by_authority = RoyaltyConfig::record_admin_action(&ctx.accounts.config, &ctx.accounts.caller.key())?;
+    let config = &mut ctx.accounts.config.load_mut()?;
+
+    let crank_due = config.snapshot_crank_enabled != 0 && epoch > config.last_snapshot_epoch;
+    require!(by_authority || crank_due, TransferHookError::Unauthorized);
+
+    config.current_snapshot_id += 1;
+    config.last_snapshot_epoch = epoch;
+
+    msg!("Snapshot {} taken at epoch {}", config.current_snapshot_id, epoch);
+
+    Ok(())
+}
+
+pub fn set_snapshot_crank(ctx: Context<crate::config::UpdateConfig>, enabled: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.require_unlocked(LOCK_RESTRICTIONS)?;
+    config.snapshot_crank_enabled = enabled as u8;
+
+    Ok(())
+}
+
+pub fn initialize_holder_snapshot(ctx: Context<InitializeHolderSnapshot>) -> Result<()> {
+    let current_snapshot_id = RoyaltyConfig::load_current(&ctx.accounts.config)?.current_snapshot_id;
+
+    let snapshot = &mut ctx.accounts.holder_snapshot.load_init()?;
+    snapshot.token_account = ctx.accounts.token_account.key();
+    snapshot.mint = ctx.accounts.mint.key();
+    snapshot.created_at_snapshot = current_snapshot_id;
+    snapshot.bump = ctx.bumps.holder_snapshot;
+
+    Ok(())
+}
+
+// Returns the token account's balance as of `snapshot_id`, for simulation or CPI callers
+pub fn get_snapshot_balance(ctx: Context<GetSnapshotBalance>, snapshot_id: u64) -> Result<u64> {
+    let current_snapshot_id = RoyaltyConfig::load_current(&ctx.accounts.config)?.current_snapshot_id;
+    require!(
+        snapshot_id > 0 && snapshot_id <= current_snapshot_id,
+        TransferHookError::SnapshotUnavailable
+    );
+
+    ctx.accounts
+        .holder_snapshot
+        .load()?
+        .balance_at(snapshot_id, ctx.accounts.token_account.amount)
+}
+
+#[derive(Accounts)]
+pub struct TakeSnapshot<'info> {
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::load_current(&config).is_ok() @ TransferHookError::ConfigVersionMismatch,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeHolderSnapshot<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        token::mint = mint,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        space = HolderSnapshot::LEN,
+        seeds = [HOLDER_SNAPSHOT_SEED, mint.key().as_ref(), token_account.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot: AccountLoader<'info, HolderSnapshot>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetSnapshotBalance<'info> {
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.mint == holder_snapshot.load()?.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        address = holder_snapshot.load()?.token_account,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    pub holder_snapshot: AccountLoader<'info, HolderSnapshot>,
+}
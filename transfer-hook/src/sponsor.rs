@@ -0,0 +1,282 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{allocate, assign, transfer, Allocate, Assign, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+use crate::holder::{HolderStats, HOLDER_STATS_SEED};
+use crate::snapshot::{HolderSnapshot, HOLDER_SNAPSHOT_SEED};
+
+pub const RENT_SPONSOR_SEED: &[u8] = b"rent-sponsor";
+
+// Project-funded lamports for per-holder PDAs, so holders can opt into state without paying rent.
+// Point `gc_rent_destination` here to recycle rent from collected holder state.
+#[account]
+pub struct RentSponsor {
+    pub mint: Pubkey,
+    pub total_funded: u64,
+    pub total_withdrawn: u64,
+    // Lamports spent on rent for sponsored accounts
+    pub total_spent: u64,
+    pub accounts_created: u64,
+    pub bump: u8,
+}
+
+impl RentSponsor {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    // Lamports above the sponsor's own rent-exempt minimum
+    pub fn available(info: &AccountInfo) -> Result<u64> {
+        let reserve = Rent::get()?.minimum_balance(info.data_len());
+        Ok(info.lamports().saturating_sub(reserve))
+    }
+}
+
+// Creates `new_account` as a program-owned PDA paid from the sponsor. A program-owned account
+// can't be the source of `create_account`, so lamports are moved directly and the PDA then
+// allocates and assigns itself. Lamports already sitting at the address count towards rent.
+fn create_sponsored_account<'info>(
+    sponsor: &mut Account<'info, RentSponsor>,
+    new_account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let sponsor_info = sponsor.to_account_info();
+    let cost = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(new_account.lamports());
+    require!(
+        cost <= RentSponsor::available(&sponsor_info)?,
+        TransferHookError::InsufficientSponsorBalance
+    );
+
+    **sponsor_info.try_borrow_mut_lamports()? -= cost;
+    **new_account.try_borrow_mut_lamports()? += cost;
+    allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: new_account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: new_account.clone(),
+            },
+            &[signer_seeds],
+        ),
+        &crate::ID,
+    )?;
+
+    sponsor.total_spent = sponsor.total_spent.saturating_add(cost);
+    sponsor.accounts_created += 1;
+
+    msg!("Sponsored {} lamports of rent for {}", cost, new_account.key);
+
+    Ok(())
+}
+
+pub fn initialize_rent_sponsor(ctx: Context<InitializeRentSponsor>) -> Result<()> {
+    let sponsor = &mut ctx.accounts.rent_sponsor;
+    sponsor.mint = ctx.accounts.mint.key();
+    sponsor.bump = ctx.bumps.rent_sponsor;
+
+    Ok(())
+}
+
+// Anyone can top up the sponsor
+pub fn fund_rent_sponsor(ctx: Context<FundRentSponsor>, lamports: u64) -> Result<()> {
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.rent_sponsor.to_account_info(),
+            },
+        ),
+        lamports,
+    )?;
+
+    let sponsor = &mut ctx.accounts.rent_sponsor;
+    sponsor.total_funded = sponsor.total_funded.saturating_add(lamports);
+
+    Ok(())
+}
+
+pub fn withdraw_rent_sponsor(ctx: Context<WithdrawRentSponsor>, lamports: u64) -> Result<()> {
+    let sponsor_info = ctx.accounts.rent_sponsor.to_account_info();
+    require!(
+        lamports <= RentSponsor::available(&sponsor_info)?,
+        TransferHookError::InsufficientSponsorBalance
+    );
+
+    **sponsor_info.try_borrow_mut_lamports()? -= lamports;
+    **ctx.accounts.authority.try_borrow_mut_lamports()? += lamports;
+
+    let sponsor = &mut ctx.accounts.rent_sponsor;
+    sponsor.total_withdrawn = sponsor.total_withdrawn.saturating_add(lamports);
+
+    Ok(())
+}
+
+// Holder stats for the signing owner, rent paid by the sponsor
+pub fn sponsor_holder_stats(ctx: Context<SponsorHolderStats>) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let mint = ctx.accounts.mint.key();
+    let holder_stats = ctx.accounts.holder_stats.to_account_info();
+    create_sponsored_account(
+        &mut ctx.accounts.rent_sponsor,
+        &holder_stats,
+        &ctx.accounts.system_program.to_account_info(),
+        HolderStats::LEN,
+        &[HOLDER_STATS_SEED, mint.as_ref(), owner.as_ref(), &[ctx.bumps.holder_stats]],
+    )?;
+
+    holder_stats.try_borrow_mut_data()?[..8].copy_from_slice(&HolderStats::DISCRIMINATOR);
+    let loader = AccountLoader::<HolderStats>::try_from(&holder_stats)?;
+    let stats = &mut loader.load_mut()?;
+    stats.owner = owner;
+    stats.mint = mint;
+    stats.last_activity_ts = Clock::get()?.unix_timestamp;
+    stats.bump = ctx.bumps.holder_stats;
+
+    Ok(())
+}
+
+// Snapshot history for a token account of the signing owner, rent paid by the sponsor
+pub fn sponsor_holder_snapshot(ctx: Context<SponsorHolderSnapshot>) -> Result<()> {
+    let current_snapshot_id = RoyaltyConfig::load_current(&ctx.accounts.config)?.current_snapshot_id;
+    let token_account = ctx.accounts.token_account.key();
+    let mint = ctx.accounts.mint.key();
+    let holder_snapshot = ctx.accounts.holder_snapshot.to_account_info();
+    create_sponsored_account(
+        &mut ctx.accounts.rent_sponsor,
+        &holder_snapshot,
+        &ctx.accounts.system_program.to_account_info(),
+        HolderSnapshot::LEN,
+        &[
+            HOLDER_SNAPSHOT_SEED,
+            mint.as_ref(),
+            token_account.as_ref(),
+            &[ctx.bumps.holder_snapshot],
+        ],
+    )?;
+
+    holder_snapshot.try_borrow_mut_data()?[..8].copy_from_slice(&HolderSnapshot::DISCRIMINATOR);
+    let loader = AccountLoader::<HolderSnapshot>::try_from(&holder_snapshot)?;
+    let snapshot = &mut loader.load_mut()?;
+    snapshot.token_account = token_account;
+    snapshot.mint = mint;
+    snapshot.created_at_snapshot = current_snapshot_id;
+    snapshot.bump = ctx.bumps.holder_snapshot;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeRentSponsor<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = RentSponsor::LEN,
+        seeds = [RENT_SPONSOR_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub rent_sponsor: Account<'info, RentSponsor>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRentSponsor<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [RENT_SPONSOR_SEED, rent_sponsor.mint.as_ref()],
+        bump = rent_sponsor.bump,
+    )]
+    pub rent_sponsor: Account<'info, RentSponsor>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRentSponsor<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [RENT_SPONSOR_SEED, config.load()?.mint.as_ref()],
+        bump = rent_sponsor.bump,
+    )]
+    pub rent_sponsor: Account<'info, RentSponsor>,
+}
+
+#[derive(Accounts)]
+pub struct SponsorHolderStats<'info> {
+    // Holders opt in themselves, so the sponsor can't be drained on arbitrary addresses
+    pub owner: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [RENT_SPONSOR_SEED, mint.key().as_ref()],
+        bump = rent_sponsor.bump,
+    )]
+    pub rent_sponsor: Account<'info, RentSponsor>,
+    /// CHECK: created in the handler, allocation fails if it already exists
+    #[account(
+        mut,
+        seeds = [HOLDER_STATS_SEED, mint.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub holder_stats: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SponsorHolderSnapshot<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        token::mint = mint,
+        token::authority = owner,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [RENT_SPONSOR_SEED, mint.key().as_ref()],
+        bump = rent_sponsor.bump,
+    )]
+    pub rent_sponsor: Account<'info, RentSponsor>,
+    /// CHECK: created in the handler, allocation fails if it already exists
+    #[account(
+        mut,
+        seeds = [HOLDER_SNAPSHOT_SEED, mint.key().as_ref(), token_account.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::config::{RoyaltyConfig, UpdateConfigWithMetaList, LOCK_RESTRICTIONS};
+use crate::error::TransferHookError;
+use crate::meta_list;
+
+// Bytes reserved for the prefix in the config layout
+pub const STAKE_SEED_PREFIX_CAPACITY: usize = 32;
+// Longest prefix that still packs into a meta's 32-byte seed config: the literal takes 2 bytes
+// plus its length, the mint key 2 and the owner read from the source account 4
+pub const MAX_STAKE_SEED_PREFIX_LEN: usize = 24;
+
+// Schema of an external staking program's per-owner stake record, so the hook can find the
+// record (PDA of `stake_program` with seeds [prefix, mint?, owner]) and read the staked amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StakeIntegrationParams {
+    pub enabled: bool,
+    pub stake_program: Pubkey,
+    pub seed_prefix: Vec<u8>,
+    pub seed_includes_mint: bool,
+    // Byte offset of the little-endian u64 staked amount in the record's data
+    pub amount_offset: u16,
+}
+
+// Staked amount recorded for the source owner, zero when the record doesn't exist
+pub fn staked_amount(stake_record: &AccountInfo, config: &RoyaltyConfig) -> Result<u64> {
+    if stake_record.owner != &config.stake_program || stake_record.data_len() == 0 {
+        return Ok(0);
+    }
+
+    let data = stake_record.try_borrow_data()?;
+    let offset = config.stake_amount_offset as usize;
+    let bytes = data
+        .get(offset..offset + 8)
+        .ok_or(TransferHookError::InvalidStakeRecord)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+pub fn set_stake_integration(
+    ctx: Context<UpdateConfigWithMetaList>,
+    params: StakeIntegrationParams,
+) -> Result<()> {
+    require!(
+        !params.seed_prefix.is_empty() && params.seed_prefix.len() <= MAX_STAKE_SEED_PREFIX_LEN,
+        TransferHookError::InvalidStakeSchema
+    );
+
+    {
+        let config = &mut ctx.accounts.config.load_mut()?;
+        config.require_unlocked(LOCK_RESTRICTIONS)?;
+        config.stake_check_enabled = params.enabled as u8;
+        config.stake_program = params.stake_program;
+        config.stake_seed_prefix = [0; STAKE_SEED_PREFIX_CAPACITY];
+        config.stake_seed_prefix[..params.seed_prefix.len()].copy_from_slice(&params.seed_prefix);
+        config.stake_seed_prefix_len = params.seed_prefix.len() as u8;
+        config.stake_seed_includes_mint = params.seed_includes_mint as u8;
+        config.stake_amount_offset = params.amount_offset;
+    }
+
+    // The stake record's derivation lives in the meta list, so it changes with the schema
+    meta_list::rewrite_from_config(
+        &ctx.accounts.config,
+        &ctx.accounts.extra_account_meta_list.to_account_info(),
+        ctx.accounts.mint.to_account_info().owner,
+    )
+}
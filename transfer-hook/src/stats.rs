@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::error::TransferHookError;
+
+pub const MINT_STATS_SEED: &[u8] = b"mint-stats";
+
+// Per-mint transfer totals, written by the hook when the account exists
+#[account(zero_copy)]
+pub struct MintStats {
+    pub total_volume: u64,
+    pub total_fees: u64,
+    pub transfer_count: u64,
+    // Volume decayed linearly over the config's dynamic fee window
+    pub rolling_volume: u64,
+    pub rolling_updated_ts: i64,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl MintStats {
+    pub const LEN: usize = 8 + std::mem::size_of::<MintStats>();
+
+    // Rolling volume as of `now`: each unit of volume fades out linearly over `window` seconds
+    pub fn decayed_volume(&self, now: i64, window: i64) -> u64 {
+        if window <= 0 {
+            return 0;
+        }
+        let elapsed = now.saturating_sub(self.rolling_updated_ts).clamp(0, window);
+        let remaining = (window - elapsed) as u128;
+        (self.rolling_volume as u128 * remaining / window as u128) as u64
+    }
+
+    pub fn record_volume(&mut self, amount: u64, now: i64, window: i64) {
+        self.rolling_volume = self.decayed_volume(now, window).saturating_add(amount);
+        self.rolling_updated_ts = now;
+        self.total_volume = self.total_volume.saturating_add(amount);
+        self.transfer_count = self.transfer_count.saturating_add(1);
+    }
+
+    pub fn record_fee(&mut self, fee: u64) {
+        self.total_fees = self.total_fees.saturating_add(fee);
+    }
+}
+
+// The hook receives the stats PDA whether or not it has been created
+pub fn load_if_initialized<'info>(
+    mint_stats: &AccountInfo<'info>,
+    mint: &Pubkey,
+) -> Result<Option<AccountLoader<'info, MintStats>>> {
+    if mint_stats.owner != &crate::ID || mint_stats.data_len() != MintStats::LEN {
+        return Ok(None);
+    }
+
+    let loader = AccountLoader::<MintStats>::try_from(mint_stats)?;
+    require_keys_eq!(
+        loader.load()?.mint,
+        *mint,
+        TransferHookError::InvalidMintStats
+    );
+    Ok(Some(loader))
+}
+
+// Permissionless, whoever creates it pays the rent
+pub fn initialize_mint_stats(ctx: Context<InitializeMintStats>) -> Result<()> {
+    let stats = &mut ctx.accounts.mint_stats.load_init()?;
+    stats.mint = ctx.accounts.mint.key();
+    stats.rolling_updated_ts = Clock::get()?.unix_timestamp;
+    stats.bump = ctx.bumps.mint_stats;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeMintStats<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = payer,
+        space = MintStats::LEN,
+        seeds = [MINT_STATS_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub mint_stats: AccountLoader<'info, MintStats>,
+    pub system_program: Program<'info, System>,
+}
+
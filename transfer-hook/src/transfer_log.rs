@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+
+pub const TRANSFER_LOG_SEED: &[u8] = b"transfer-log";
+
+pub const MAX_TRANSFER_LOG_CAPACITY: u32 = 128;
+
+// A single instruction can allocate or grow an account by at most 10KiB, so a log is created with
+// up to 112 entries and grown to the full capacity with `resize_transfer_log`
+pub const MAX_INITIAL_TRANSFER_LOG_CAPACITY: u32 = 112;
+
+// Header of the optional per-mint ring buffer, `capacity` entries follow it in the account data
+#[account(zero_copy)]
+pub struct TransferLog {
+    // Entries ever appended, the next write goes to `total_entries % capacity`
+    pub total_entries: u64,
+    pub mint: Pubkey,
+    pub capacity: u32,
+    pub bump: u8,
+    pub _padding: [u8; 3],
+}
+
+#[zero_copy]
+pub struct TransferLogEntry {
+    pub slot: u64,
+    pub amount: u64,
+    pub fee: u64,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+}
+
+impl TransferLog {
+    pub const HEADER_LEN: usize = 8 + std::mem::size_of::<TransferLog>();
+    pub const ENTRY_LEN: usize = std::mem::size_of::<TransferLogEntry>();
+
+    pub fn space(capacity: u32) -> usize {
+        Self::HEADER_LEN + capacity as usize * Self::ENTRY_LEN
+    }
+}
+
+// The hook receives the log PDA whether or not the mint enabled it, so an uninitialized account
+// is skipped
+pub fn append_if_initialized(
+    transfer_log: &AccountInfo,
+    mint: &Pubkey,
+    entry: TransferLogEntry,
+) -> Result<()> {
+    if transfer_log.owner != &crate::ID || transfer_log.data_len() < TransferLog::HEADER_LEN {
+        return Ok(());
+    }
+
+    let mut data = transfer_log.try_borrow_mut_data()?;
+    require!(
+        data[..8] == TransferLog::DISCRIMINATOR,
+        TransferHookError::InvalidTransferLog
+    );
+
+    let (header_bytes, entries) = data[8..].split_at_mut(TransferLog::HEADER_LEN - 8);
+    let header: &mut TransferLog = bytemuck::from_bytes_mut(header_bytes);
+    require_keys_eq!(header.mint, *mint, TransferHookError::InvalidTransferLog);
+    if header.capacity == 0 {
+        return Ok(());
+    }
+
+    let index = (header.total_entries % header.capacity as u64) as usize;
+    let start = index * TransferLog::ENTRY_LEN;
+    *bytemuck::from_bytes_mut::<TransferLogEntry>(&mut entries[start..start + TransferLog::ENTRY_LEN]) =
+        entry;
+    header.total_entries += 1;
+
+    Ok(())
+}
+
+pub fn initialize_transfer_log(ctx: Context<InitializeTransferLog>, capacity: u32) -> Result<()> {
+    require!(
+        capacity > 0 && capacity <= MAX_INITIAL_TRANSFER_LOG_CAPACITY,
+        TransferHookError::InvalidTransferLogCapacity
+    );
+
+    let log = &mut ctx.accounts.transfer_log.load_init()?;
+    log.mint = ctx.accounts.mint.key();
+    log.capacity = capacity;
+    log.bump = ctx.bumps.transfer_log;
+
+    Ok(())
+}
+
+// Ring order doesn't survive a capacity change, so resizing clears the log
+pub fn resize_transfer_log(ctx: Context<ResizeTransferLog>, capacity: u32) -> Result<()> {
+    require!(
+        capacity > 0 && capacity <= MAX_TRANSFER_LOG_CAPACITY,
+        TransferHookError::InvalidTransferLogCapacity
+    );
+
+    let log = &mut ctx.accounts.transfer_log.load_mut()?;
+    log.capacity = capacity;
+    log.total_entries = 0;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u32)]
+pub struct InitializeTransferLog<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = TransferLog::space(capacity),
+        seeds = [TRANSFER_LOG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub transfer_log: AccountLoader<'info, TransferLog>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u32)]
+pub struct ResizeTransferLog<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == transfer_log.load()?.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        realloc = TransferLog::space(capacity),
+        realloc::payer = authority,
+        realloc::zero = true,
+    )]
+    pub transfer_log: AccountLoader<'info, TransferLog>,
+    pub system_program: Program<'info, System>,
+}
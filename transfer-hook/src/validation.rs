@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_spl::token_2022::spl_token_2022::state::AccountState;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::error::TransferHookError;
+use crate::extensions;
+
+// Checks on the account royalties are paid into, run whenever a royalty is charged or paid.
+// Without them a bad account only shows when the owner's payment fails, after the royalty is owed.
+//
+// Closed or uninitialized accounts are already rejected when the accounts are deserialized.
+// Destinations are checked by token2022 before the hook runs. The royalty account is fixed in
+// config for every later transfer, so it has to carry the immutable owner extension: otherwise its
+// owner could be reassigned after it was configured and redirect all future royalties.
+
+pub fn validate_royalty_account(royalty_account: &InterfaceAccount<TokenAccount>, mint: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        royalty_account.mint,
+        *mint,
+        TransferHookError::RoyaltyAccountMintMismatch
+    );
+    require!(
+        royalty_account.state != AccountState::Frozen,
+        TransferHookError::RoyaltyAccountFrozen
+    );
+    require!(
+        extensions::has_immutable_owner(&royalty_account.to_account_info())?,
+        TransferHookError::RoyaltyAccountOwnerMutable
+    );
+
+    Ok(())
+}
+
+// PDAs resolved from the meta list are trusted only once re-derived from this transfer, so
+// hand-built Execute accounts can't substitute another mint's or owner's PDA
+pub fn require_derived(account: &AccountInfo, seeds: &[&[u8]], bump: u8) -> Result<()> {
+    let bump = [bump];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump);
+    let expected = Pubkey::create_program_address(&signer_seeds, &crate::ID)
+        .map_err(|_| TransferHookError::UnexpectedExtraAccount)?;
+    require_keys_eq!(
+        *account.key,
+        expected,
+        TransferHookError::UnexpectedExtraAccount
+    );
+
+    Ok(())
+}
+
+// Token2022 CPIs Execute, so the hook always runs below the transaction level. This holds for every
+// mint without relying on optional state. Nesting Execute under itself (this program -> token2022
+// -> this program) needs no guard, the runtime rejects it as reentrancy.
+pub fn require_invoked_by_cpi() -> Result<()> {
+    require!(
+        is_cpi_stack_height(get_stack_height()),
+        TransferHookError::HookCalledDirectly
+    );
+
+    Ok(())
+}
+
+fn is_cpi_stack_height(stack_height: usize) -> bool {
+    stack_height > TRANSACTION_LEVEL_STACK_HEIGHT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_level_execute_is_rejected() {
+        assert!(!is_cpi_stack_height(TRANSACTION_LEVEL_STACK_HEIGHT));
+    }
+
+    #[test]
+    fn execute_from_token2022_is_allowed() {
+        // Token2022 called by the transaction, or by another program
+        assert!(is_cpi_stack_height(TRANSACTION_LEVEL_STACK_HEIGHT + 1));
+        assert!(is_cpi_stack_height(TRANSACTION_LEVEL_STACK_HEIGHT + 3));
+    }
+}
@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::spl_token_2022::extension::transfer_fee::instruction::{
+        harvest_withheld_tokens_to_mint, withdraw_withheld_tokens_from_mint,
+    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::accounting::{self, VaultFlow, ROYALTY_ACCOUNTING_SEED};
+use crate::config::{RoyaltyConfig, LOCK_RECIPIENTS};
+use crate::error::TransferHookError;
+use crate::meta_list::{self, META_LIST_SEED};
+use crate::{ledger, rewards};
+
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault-authority";
+
+// Vault tokens already promised to creators and to holder rewards. Outflows that aren't paying
+// those out may only spend the balance above this.
+pub fn reserved_balance(creator_ledger: &AccountInfo, rewards_pool: &AccountInfo) -> Result<u64> {
+    Ok(ledger::outstanding_if_initialized(creator_ledger)?
+        .saturating_add(rewards::pending_if_initialized(rewards_pool)?))
+}
+
+// Creates the royalty vault as the ATA of a program PDA, so fees land in an account the program
+// controls, and makes it the recipient. The vault authority can't co-sign `accept_recipient`, the
+// authority creating the account stands in for the two-step rotation.
+pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
+    {
+        let config = &mut ctx.accounts.config.load_mut()?;
+        config.require_unlocked(LOCK_RECIPIENTS)?;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            config.rotation_allowed(now),
+            TransferHookError::RotationCooldownActive
+        );
+
+        config.royalty_vault = ctx.accounts.royalty_vault.key();
+        config.vault_authority_bump = ctx.bumps.vault_authority;
+        config.royalty_recipient = config.royalty_vault;
+        config.pending_recipient = Pubkey::default();
+        config.last_rotation_ts = now;
+    }
+
+    // Point the meta list at the vault so token2022 resolves it on the next transfer. Before the
+    // list exists there is nothing to rewrite, pass the vault to `initialize_extra_account_meta_list`.
+    let extra_account_meta_list = ctx.accounts.extra_account_meta_list.to_account_info();
+    if extra_account_meta_list.owner != &crate::ID {
+        return Ok(());
+    }
+    meta_list::rewrite_from_config(
+        &ctx.accounts.config,
+        &extra_account_meta_list,
+        ctx.accounts.mint.to_account_info().owner,
+    )
+}
+
+// Permissionless: sweeps transfer-fee extension withholdings from the given token accounts
+// (remaining accounts) into the mint, then from the mint into the royalty vault. Requires the
+// mint's withdraw-withheld authority to be the vault authority PDA.
+pub fn harvest_withheld_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, HarvestWithheldFees<'info>>,
+) -> Result<()> {
+    let token_program = ctx.accounts.token_program.key();
+    let mint = ctx.accounts.mint.to_account_info();
+
+    if !ctx.remaining_accounts.is_empty() {
+        let sources: Vec<&Pubkey> = ctx.remaining_accounts.iter().map(|account| account.key).collect();
+        let mut account_infos = vec![mint.clone()];
+        account_infos.extend_from_slice(ctx.remaining_accounts);
+        invoke(
+            &harvest_withheld_tokens_to_mint(&token_program, mint.key, &sources)?,
+            &account_infos,
+        )?;
+    }
+
+    let vault_before = ctx.accounts.royalty_vault.amount;
+    let mint_key = ctx.accounts.mint.key();
+    let bump = ctx.accounts.config.load()?.vault_authority_bump;
+    let signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, mint_key.as_ref(), &[bump]]];
+    invoke_signed(
+        &withdraw_withheld_tokens_from_mint(
+            &token_program,
+            mint.key,
+            &ctx.accounts.royalty_vault.key(),
+            &ctx.accounts.vault_authority.key(),
+            &[],
+        )?,
+        &[
+            mint,
+            ctx.accounts.royalty_vault.to_account_info(),
+            ctx.accounts.vault_authority.to_account_info(),
+        ],
+        signer_seeds,
+    )?;
+
+    // Reconcile against the vault balance rather than trusting the mint's withheld figure
+    ctx.accounts.royalty_vault.reload()?;
+    let harvested = ctx.accounts.royalty_vault.amount.saturating_sub(vault_before);
+    accounting::record_vault_flow_if_initialized(
+        &ctx.accounts.royalty_accounting.to_account_info(),
+        &mint_key,
+        VaultFlow::Harvest,
+        harvested,
+    )?;
+    let config = &mut ctx.accounts.config.load_mut()?;
+    config.total_extension_fees_harvested = config
+        .total_extension_fees_harvested
+        .saturating_add(harvested);
+
+    msg!(
+        "Harvested {} withheld tokens, {} total",
+        harvested,
+        config.total_extension_fees_harvested
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the royalty vault, holds no data
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = mint,
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [META_LIST_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestWithheldFees<'info> {
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == mint.key(),
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: PDA owning the royalty vault, signs the withdrawal
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = config.load()?.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
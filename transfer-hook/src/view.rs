@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::amm::TradeSide;
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+use crate::ledger::{self, CREATOR_LEDGER_SEED};
+use crate::rewards::{self, REWARDS_POOL_SEED};
+use crate::stats::{self, MINT_STATS_SEED};
+
+// Bumped whenever `HookState` changes. Fields are only ever appended, so callers decoding an older
+// format can read a newer one by ignoring trailing bytes.
+pub const STATE_FORMAT_VERSION: u8 = 1;
+
+// Snapshot of a mint's hook state, returned by `get_state` as Borsh-encoded return data so callers
+// don't depend on account layouts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct HookState {
+    pub format_version: u8,
+    pub config_version: u8,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub royalty_recipient: Pubkey,
+    pub royalty_vault: Pubkey,
+    // Rate a plain transfer is charged right now, before tiers and the volume controller
+    pub fee_bps: u16,
+    pub min_fee_amount: u64,
+    pub min_fee: u64,
+    pub max_fee: u64,
+    pub locked_fields: u16,
+    pub monitor_only_rules: u16,
+    // Stats fields are zero when the mint has no stats account
+    pub has_stats: bool,
+    pub total_volume: u64,
+    pub total_fees: u64,
+    pub transfer_count: u64,
+    pub rolling_volume: u64,
+    // Zero until the vault is initialized
+    pub vault_balance: u64,
+    // Parts of the vault balance owed to creators and rewards
+    pub creator_owed: u64,
+    pub rewards_pending: u64,
+}
+
+// Read-only, for `simulateTransaction` or CPI callers
+pub fn get_state(ctx: Context<GetState>) -> Result<HookState> {
+    let config = RoyaltyConfig::load_current(&ctx.accounts.config)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    let mut state = HookState {
+        format_version: STATE_FORMAT_VERSION,
+        config_version: config.version,
+        mint: config.mint,
+        authority: config.authority,
+        royalty_recipient: config.royalty_recipient,
+        royalty_vault: config.royalty_vault,
+        fee_bps: config.base_fee_bps(now, TradeSide::Transfer),
+        min_fee_amount: config.min_fee_amount,
+        min_fee: config.min_fee,
+        max_fee: config.max_fee,
+        locked_fields: config.locked_fields,
+        monitor_only_rules: config.monitor_only_rules,
+        has_stats: false,
+        total_volume: 0,
+        total_fees: 0,
+        transfer_count: 0,
+        rolling_volume: 0,
+        vault_balance: 0,
+        creator_owed: ledger::outstanding_if_initialized(&ctx.accounts.creator_ledger.to_account_info())?,
+        rewards_pending: rewards::pending_if_initialized(&ctx.accounts.rewards_pool.to_account_info())?,
+    };
+
+    if let Some(mint_stats) = stats::load_if_initialized(&ctx.accounts.mint_stats.to_account_info(), &config.mint)? {
+        let mint_stats = mint_stats.load()?;
+        state.has_stats = true;
+        state.total_volume = mint_stats.total_volume;
+        state.total_fees = mint_stats.total_fees;
+        state.transfer_count = mint_stats.transfer_count;
+        state.rolling_volume = mint_stats.decayed_volume(now, config.dynamic_volume_window);
+    }
+    if let Some(royalty_vault) = &ctx.accounts.royalty_vault {
+        state.vault_balance = royalty_vault.amount;
+    }
+
+    Ok(state)
+}
+
+#[derive(Accounts)]
+pub struct GetState<'info> {
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    /// CHECK: mint stats PDA, read when initialized
+    #[account(
+        seeds = [MINT_STATS_SEED, config.load()?.mint.as_ref()],
+        bump,
+    )]
+    pub mint_stats: UncheckedAccount<'info>,
+    /// CHECK: creator ledger PDA, read when initialized
+    #[account(
+        seeds = [CREATOR_LEDGER_SEED, config.load()?.mint.as_ref()],
+        bump,
+    )]
+    pub creator_ledger: UncheckedAccount<'info>,
+    /// CHECK: rewards pool PDA, read when initialized
+    #[account(
+        seeds = [REWARDS_POOL_SEED, config.load()?.mint.as_ref()],
+        bump,
+    )]
+    pub rewards_pool: UncheckedAccount<'info>,
+    // Omit before `initialize_vault`
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+    )]
+    pub royalty_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+}
@@ -0,0 +1,379 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::accounting::{self, VaultFlow, ROYALTY_ACCOUNTING_SEED};
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+use crate::ledger::CREATOR_LEDGER_SEED;
+use crate::outflow::Outflow;
+use crate::rewards::REWARDS_POOL_SEED;
+use crate::vault::{self, VAULT_AUTHORITY_SEED};
+
+pub const WITHDRAWAL_POLICY_SEED: &[u8] = b"withdrawal-policy";
+pub const PENDING_WITHDRAWAL_SEED: &[u8] = b"pending-withdrawal";
+
+// Approvals are tracked as a bitmask over the approver list
+pub const MAX_APPROVERS: usize = 8;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WithdrawalPolicyParams {
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+    // Seconds between proposal and earliest execution
+    pub timelock: i64,
+}
+
+// Vault withdrawals need `threshold` of `approvers` and must wait out the timelock
+#[account]
+pub struct WithdrawalPolicy {
+    pub mint: Pubkey,
+    pub approvers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timelock: i64,
+    pub proposal_count: u64,
+    // Bumped on every policy change, pending withdrawals from an older policy can't be executed
+    pub policy_nonce: u64,
+    pub bump: u8,
+}
+
+impl WithdrawalPolicy {
+    pub const LEN: usize = 8 + 32 + (4 + 32 * MAX_APPROVERS) + 1 + 8 + 8 + 8 + 1;
+
+    fn apply(&mut self, params: WithdrawalPolicyParams) -> Result<()> {
+        let approvers = &params.approvers;
+        require!(
+            approvers.len() <= MAX_APPROVERS
+                && params.threshold > 0
+                && params.threshold as usize <= approvers.len()
+                && approvers
+                    .iter()
+                    .enumerate()
+                    .all(|(i, approver)| !approvers[..i].contains(approver)),
+            TransferHookError::InvalidWithdrawalPolicy
+        );
+        require!(params.timelock >= 0, TransferHookError::InvalidCooldown);
+
+        self.approvers = params.approvers;
+        self.threshold = params.threshold;
+        self.timelock = params.timelock;
+        self.policy_nonce += 1;
+        Ok(())
+    }
+
+    pub fn approver_index(&self, approver: &Pubkey) -> Option<usize> {
+        self.approvers.iter().position(|candidate| candidate == approver)
+    }
+}
+
+#[account]
+pub struct PendingWithdrawal {
+    pub mint: Pubkey,
+    pub id: u64,
+    pub policy_nonce: u64,
+    pub destination: Pubkey,
+    pub amount: u64,
+    // Refunded the rent when the withdrawal is executed
+    pub proposer: Pubkey,
+    pub proposed_at: i64,
+    // Bit i set when `approvers[i]` has approved
+    pub approvals: u8,
+    pub bump: u8,
+}
+
+impl PendingWithdrawal {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 32 + 8 + 32 + 8 + 1 + 1;
+
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
+}
+
+pub fn initialize_withdrawal_policy(
+    ctx: Context<InitializeWithdrawalPolicy>,
+    params: WithdrawalPolicyParams,
+) -> Result<()> {
+    let policy = &mut ctx.accounts.withdrawal_policy;
+    policy.mint = ctx.accounts.mint.key();
+    policy.bump = ctx.bumps.withdrawal_policy;
+    policy.apply(params)
+}
+
+pub fn update_withdrawal_policy(
+    ctx: Context<UpdateWithdrawalPolicy>,
+    params: WithdrawalPolicyParams,
+) -> Result<()> {
+    ctx.accounts.withdrawal_policy.apply(params)?;
+
+    // Voids withdrawals proposed under the previous approver list
+    msg!(
+        "Withdrawal policy updated to nonce {}",
+        ctx.accounts.withdrawal_policy.policy_nonce
+    );
+
+    Ok(())
+}
+
+// Any approver or the config authority can propose, proposing does not count as approval
+pub fn propose_withdrawal(ctx: Context<ProposeWithdrawal>, amount: u64) -> Result<()> {
+    let proposer = ctx.accounts.proposer.key();
+    let policy = &mut ctx.accounts.withdrawal_policy;
+    require!(
+        policy.approver_index(&proposer).is_some()
+            || RoyaltyConfig::record_admin_action(&ctx.accounts.config, &proposer)?,
+        TransferHookError::Unauthorized
+    );
+
+    let pending = &mut ctx.accounts.pending_withdrawal;
+    pending.mint = policy.mint;
+    pending.id = policy.proposal_count;
+    pending.policy_nonce = policy.policy_nonce;
+    pending.destination = ctx.accounts.destination.key();
+    pending.amount = amount;
+    pending.proposer = proposer;
+    pending.proposed_at = Clock::get()?.unix_timestamp;
+    pending.bump = ctx.bumps.pending_withdrawal;
+    policy.proposal_count += 1;
+
+    msg!(
+        "Proposed withdrawal {} of {} tokens to {}",
+        pending.id,
+        amount,
+        pending.destination
+    );
+
+    Ok(())
+}
+
+pub fn approve_withdrawal(ctx: Context<ApproveWithdrawal>) -> Result<()> {
+    let policy = &ctx.accounts.withdrawal_policy;
+    let pending = &mut ctx.accounts.pending_withdrawal;
+    require!(
+        pending.policy_nonce == policy.policy_nonce,
+        TransferHookError::WithdrawalPolicyChanged
+    );
+    let index = policy
+        .approver_index(&ctx.accounts.approver.key())
+        .ok_or(TransferHookError::Unauthorized)?;
+    let bit = 1u8 << index;
+    require!(
+        pending.approvals & bit == 0,
+        TransferHookError::WithdrawalAlreadyApproved
+    );
+
+    pending.approvals |= bit;
+
+    msg!(
+        "Withdrawal {} approved {}/{}",
+        pending.id,
+        pending.approval_count(),
+        policy.threshold
+    );
+
+    Ok(())
+}
+
+// Permissionless once approved and past the timelock. Approves the signing executor for the amount,
+// the executor's transfer to the destination has to follow in the same transaction.
+pub fn execute_withdrawal<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteWithdrawal<'info>>) -> Result<()> {
+    let policy = &ctx.accounts.withdrawal_policy;
+    let pending = &ctx.accounts.pending_withdrawal;
+    require!(
+        pending.policy_nonce == policy.policy_nonce,
+        TransferHookError::WithdrawalPolicyChanged
+    );
+    require!(
+        pending.approval_count() >= policy.threshold as u32
+            && Clock::get()?.unix_timestamp.saturating_sub(pending.proposed_at) >= policy.timelock,
+        TransferHookError::WithdrawalNotReady
+    );
+    // Balances owed to creators and unfunded holder rewards can't be withdrawn
+    let reserved = vault::reserved_balance(
+        &ctx.accounts.creator_ledger.to_account_info(),
+        &ctx.accounts.rewards_pool.to_account_info(),
+    )?;
+    require!(
+        pending.amount <= ctx.accounts.royalty_vault.amount.saturating_sub(reserved),
+        TransferHookError::InsufficientVaultBalance
+    );
+
+    let mint_key = ctx.accounts.mint.key();
+    let bump = ctx.accounts.config.load()?.vault_authority_bump;
+    Outflow {
+        token_program: &ctx.accounts.token_program.to_account_info(),
+        source: &ctx.accounts.royalty_vault.to_account_info(),
+        mint: &ctx.accounts.mint.to_account_info(),
+        decimals: ctx.accounts.mint.decimals,
+        owner: &ctx.accounts.vault_authority.to_account_info(),
+        delegate: &ctx.accounts.executor.to_account_info(),
+        instructions: &ctx.accounts.instructions.to_account_info(),
+    }
+    .approve(
+        &[(pending.destination, pending.amount)],
+        &[&[VAULT_AUTHORITY_SEED, mint_key.as_ref(), &[bump]]],
+    )?;
+    accounting::record_vault_flow_if_initialized(
+        &ctx.accounts.royalty_accounting.to_account_info(),
+        &mint_key,
+        VaultFlow::Withdrawal,
+        pending.amount,
+    )?;
+
+    msg!(
+        "Executed withdrawal {} of {} tokens to {}",
+        pending.id,
+        pending.amount,
+        pending.destination
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeWithdrawalPolicy<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = WithdrawalPolicy::LEN,
+        seeds = [WITHDRAWAL_POLICY_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_policy: Account<'info, WithdrawalPolicy>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateWithdrawalPolicy<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_POLICY_SEED, config.load()?.mint.as_ref()],
+        bump = withdrawal_policy.bump,
+    )]
+    pub withdrawal_policy: Account<'info, WithdrawalPolicy>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeWithdrawal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::load_current(&config)?.mint == withdrawal_policy.mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_POLICY_SEED, withdrawal_policy.mint.as_ref()],
+        bump = withdrawal_policy.bump,
+    )]
+    pub withdrawal_policy: Account<'info, WithdrawalPolicy>,
+    #[account(
+        init,
+        payer = proposer,
+        space = PendingWithdrawal::LEN,
+        seeds = [
+            PENDING_WITHDRAWAL_SEED,
+            withdrawal_policy.mint.as_ref(),
+            &withdrawal_policy.proposal_count.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    #[account(
+        token::mint = withdrawal_policy.mint,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWithdrawal<'info> {
+    pub approver: Signer<'info>,
+    #[account(
+        seeds = [WITHDRAWAL_POLICY_SEED, withdrawal_policy.mint.as_ref()],
+        bump = withdrawal_policy.bump,
+    )]
+    pub withdrawal_policy: Account<'info, WithdrawalPolicy>,
+    #[account(
+        mut,
+        seeds = [PENDING_WITHDRAWAL_SEED, withdrawal_policy.mint.as_ref(), &pending_withdrawal.id.to_le_bytes()],
+        bump = pending_withdrawal.bump,
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    // Anyone, moves the tokens as the vault's delegate
+    pub executor: Signer<'info>,
+    #[account(
+        constraint = RoyaltyConfig::load_current(&config)?.royalty_vault == royalty_vault.key() @ TransferHookError::InvalidRoyaltyRecipient,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [WITHDRAWAL_POLICY_SEED, mint.key().as_ref()],
+        bump = withdrawal_policy.bump,
+    )]
+    pub withdrawal_policy: Account<'info, WithdrawalPolicy>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_WITHDRAWAL_SEED, mint.key().as_ref(), &pending_withdrawal.id.to_le_bytes()],
+        bump = pending_withdrawal.bump,
+        has_one = proposer @ TransferHookError::InvalidRentDestination,
+        has_one = destination,
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    /// CHECK: receives the pending withdrawal's rent, checked against it
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+    /// CHECK: PDA owning the royalty vault, signs the approval
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = config.load()?.vault_authority_bump,
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub royalty_vault: InterfaceAccount<'info, TokenAccount>,
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: creator ledger PDA, read when initialized so unclaimed balances aren't withdrawn
+    #[account(
+        seeds = [CREATOR_LEDGER_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub creator_ledger: UncheckedAccount<'info>,
+    /// CHECK: rewards pool PDA, read when initialized so unfunded rewards aren't withdrawn
+    #[account(
+        seeds = [REWARDS_POOL_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub rewards_pool: UncheckedAccount<'info>,
+    /// CHECK: royalty accounting PDA, only written when initialized and owned by this program
+    #[account(
+        mut,
+        seeds = [ROYALTY_ACCOUNTING_SEED, mint.key().as_ref()],
+        bump,
+    )]
+    pub royalty_accounting: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, the executor's transfer is checked against it
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
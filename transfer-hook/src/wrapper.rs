@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::config::RoyaltyConfig;
+use crate::error::TransferHookError;
+
+pub const WRAPPER_SEED: &[u8] = b"wrapper";
+pub const WRAPPER_AUTHORITY_SEED: &[u8] = b"wrapper-authority";
+
+// 1:1 bridge from a legacy SPL mint into the hooked mint. The wrapper authority PDA holds the
+// legacy tokens and is the hooked mint's mint authority, so hooked supply can only grow by wrapping.
+#[account]
+pub struct Wrapper {
+    pub legacy_mint: Pubkey,
+    pub hooked_mint: Pubkey,
+    // ATA of the wrapper authority for the legacy mint
+    pub legacy_vault: Pubkey,
+    pub total_wrapped: u64,
+    pub total_unwrapped: u64,
+    pub bump: u8,
+    pub authority_bump: u8,
+}
+
+impl Wrapper {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1 + 1;
+}
+
+// Every hooked token in circulation must be backed by a locked legacy token. Holders can burn
+// hooked tokens themselves, so the vault may hold more than the supply but never less.
+fn require_backed(legacy_vault: &TokenAccount, hooked_mint: &Mint) -> Result<()> {
+    require!(
+        legacy_vault.amount >= hooked_mint.supply,
+        TransferHookError::WrapperUndercollateralized
+    );
+
+    Ok(())
+}
+
+pub fn initialize_wrapper(ctx: Context<InitializeWrapper>) -> Result<()> {
+    let wrapper = &mut ctx.accounts.wrapper;
+    wrapper.legacy_mint = ctx.accounts.legacy_mint.key();
+    wrapper.hooked_mint = ctx.accounts.mint.key();
+    wrapper.legacy_vault = ctx.accounts.legacy_vault.key();
+    wrapper.bump = ctx.bumps.wrapper;
+    wrapper.authority_bump = ctx.bumps.wrapper_authority;
+
+    Ok(())
+}
+
+// Locks `amount` legacy tokens and mints the amount the vault actually received, so a legacy
+// mint with its own transfer fee can't be used to mint unbacked tokens
+pub fn wrap(ctx: Context<Wrap>, amount: u64) -> Result<()> {
+    let vault_before = ctx.accounts.legacy_vault.amount;
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.legacy_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.legacy_token_account.to_account_info(),
+                mint: ctx.accounts.legacy_mint.to_account_info(),
+                to: ctx.accounts.legacy_vault.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+        ctx.accounts.legacy_mint.decimals,
+    )?;
+    ctx.accounts.legacy_vault.reload()?;
+    let received = ctx.accounts.legacy_vault.amount.saturating_sub(vault_before);
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        WRAPPER_AUTHORITY_SEED,
+        mint_key.as_ref(),
+        &[ctx.accounts.wrapper.authority_bump],
+    ]];
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.token_account.to_account_info(),
+                authority: ctx.accounts.wrapper_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        received,
+    )?;
+
+    ctx.accounts.mint.reload()?;
+    require_backed(&ctx.accounts.legacy_vault, &ctx.accounts.mint)?;
+
+    let wrapper = &mut ctx.accounts.wrapper;
+    wrapper.total_wrapped = wrapper.total_wrapped.saturating_add(received);
+
+    msg!("Wrapped {} legacy tokens", received);
+
+    Ok(())
+}
+
+// Burns `amount` hooked tokens and releases as many legacy tokens. Burning doesn't go through the
+// transfer hook, unwrapping is not charged royalties.
+pub fn unwrap(ctx: Context<Unwrap>, amount: u64) -> Result<()> {
+    token_interface::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        WRAPPER_AUTHORITY_SEED,
+        mint_key.as_ref(),
+        &[ctx.accounts.wrapper.authority_bump],
+    ]];
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.legacy_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.legacy_vault.to_account_info(),
+                mint: ctx.accounts.legacy_mint.to_account_info(),
+                to: ctx.accounts.legacy_token_account.to_account_info(),
+                authority: ctx.accounts.wrapper_authority.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.legacy_mint.decimals,
+    )?;
+
+    ctx.accounts.legacy_vault.reload()?;
+    ctx.accounts.mint.reload()?;
+    require_backed(&ctx.accounts.legacy_vault, &ctx.accounts.mint)?;
+
+    let wrapper = &mut ctx.accounts.wrapper;
+    wrapper.total_unwrapped = wrapper.total_unwrapped.saturating_add(amount);
+
+    msg!("Unwrapped {} hooked tokens", amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeWrapper<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        constraint = RoyaltyConfig::record_admin_action(&config, &authority.key())? @ TransferHookError::Unauthorized,
+        has_one = mint,
+    )]
+    pub config: AccountLoader<'info, RoyaltyConfig>,
+    // Minting has to be handed to the wrapper authority first, so supply can't bypass the vault
+    #[account(
+        constraint = mint.mint_authority == COption::Some(wrapper_authority.key()) @ TransferHookError::InvalidWrapper,
+        constraint = mint.decimals == legacy_mint.decimals @ TransferHookError::InvalidWrapper,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mint::token_program = legacy_token_program,
+    )]
+    pub legacy_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = authority,
+        space = Wrapper::LEN,
+        seeds = [WRAPPER_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub wrapper: Account<'info, Wrapper>,
+    /// CHECK: PDA holding the legacy vault and minting hooked tokens, holds no data
+    #[account(
+        seeds = [WRAPPER_AUTHORITY_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub wrapper_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = legacy_mint,
+        associated_token::authority = wrapper_authority,
+        associated_token::token_program = legacy_token_program,
+    )]
+    pub legacy_vault: InterfaceAccount<'info, TokenAccount>,
+    pub legacy_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Wrap<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [WRAPPER_SEED, mint.key().as_ref()],
+        bump = wrapper.bump,
+        has_one = legacy_mint @ TransferHookError::InvalidWrapper,
+        has_one = legacy_vault @ TransferHookError::InvalidWrapper,
+        constraint = wrapper.hooked_mint == mint.key() @ TransferHookError::InvalidWrapper,
+    )]
+    pub wrapper: Account<'info, Wrapper>,
+    /// CHECK: PDA holding the legacy vault and minting hooked tokens
+    #[account(
+        seeds = [WRAPPER_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = wrapper.authority_bump,
+    )]
+    pub wrapper_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub legacy_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub legacy_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = legacy_mint,
+        token::authority = owner,
+    )]
+    pub legacy_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    pub legacy_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Unwrap<'info> {
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [WRAPPER_SEED, mint.key().as_ref()],
+        bump = wrapper.bump,
+        has_one = legacy_mint @ TransferHookError::InvalidWrapper,
+        has_one = legacy_vault @ TransferHookError::InvalidWrapper,
+        constraint = wrapper.hooked_mint == mint.key() @ TransferHookError::InvalidWrapper,
+    )]
+    pub wrapper: Account<'info, Wrapper>,
+    /// CHECK: PDA holding the legacy vault and minting hooked tokens
+    #[account(
+        seeds = [WRAPPER_AUTHORITY_SEED, mint.key().as_ref()],
+        bump = wrapper.authority_bump,
+    )]
+    pub wrapper_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub legacy_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub legacy_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = legacy_mint,
+    )]
+    pub legacy_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = owner,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    pub legacy_token_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+}